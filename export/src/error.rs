@@ -0,0 +1,51 @@
+/// Error raised while reading records or writing an export
+#[derive(Debug)]
+pub struct ExportError {
+    pub message: String,
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Export error: {}", self.message)
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<&str> for ExportError {
+    fn from(message: &str) -> Self {
+        ExportError {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<String> for ExportError {
+    fn from(message: String) -> Self {
+        ExportError { message }
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(err: std::io::Error) -> Self {
+        ExportError {
+            message: format!("I/O error: {}", err),
+        }
+    }
+}
+
+impl From<prost::DecodeError> for ExportError {
+    fn from(err: prost::DecodeError) -> Self {
+        ExportError {
+            message: format!("Protobuf decode error: {}", err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(err: serde_json::Error) -> Self {
+        ExportError {
+            message: format!("JSON error: {}", err),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Readers for decoded `map_program_data` outputs.
+//!
+//! Two inputs are supported:
+//! - binary files holding one protobuf `Data` message each
+//! - JSONL with one `Data` per line, either bare or wrapped as the `@data` of
+//!   a `substreams run -o jsonl` line; keys may be snake_case or camelCase
+//!   and 64-bit integers numbers or strings, as protojson writes them
+//!
+//! Only the records needed to rebuild mails are read from JSON.
+
+use std::fs;
+use std::path::Path;
+
+use prost::Message as _;
+use serde_json::Value;
+
+use crate::error::ExportError;
+use crate::pb::{CreatemailInstruction, Data, MailV2UpdateEventEvent, SendmailInstruction, UpdatemailInstruction};
+
+/// Read every `Data` of a file, JSONL when its extension is `.jsonl` or
+/// `.json`, a binary message otherwise
+pub fn read_file(path: &Path) -> Result<Vec<Data>, ExportError> {
+    let is_json = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("jsonl") || extension.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        let text = fs::read_to_string(path)?;
+        parse_jsonl(&text).map_err(|err| format!("{}: {}", path.display(), err.message).into())
+    } else {
+        let bytes = fs::read(path)?;
+        let data = Data::decode(bytes.as_slice())
+            .map_err(|err| ExportError::from(format!("{}: {}", path.display(), err)))?;
+        Ok(vec![data])
+    }
+}
+
+/// Parse JSONL text, skipping blank lines
+pub fn parse_jsonl(text: &str) -> Result<Vec<Data>, ExportError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let value: Value = serde_json::from_str(line)
+                .map_err(|err| ExportError::from(format!("line {}: {}", number + 1, err)))?;
+            Ok(data_from_json(value.get("@data").unwrap_or(&value)))
+        })
+        .collect()
+}
+
+fn data_from_json(value: &Value) -> Data {
+    Data {
+        slot: u64_field(value, "slot"),
+        block_time: u64_field(value, "block_time") as i64,
+        createmail_instruction_list: list(value, "createmail_instruction_list")
+            .map(|record| CreatemailInstruction {
+                trx_hash: str_field(record, "trx_hash"),
+                subject: str_field(record, "subject"),
+                from: str_field(record, "from"),
+                to: str_field(record, "to"),
+                salt: str_field(record, "salt"),
+                iv: str_field(record, "iv"),
+                version: str_field(record, "version"),
+                parent_id: str_field(record, "parent_id"),
+                acct_mail: str_field(record, "acct_mail"),
+                mail_id: str_field(record, "mail_id"),
+                ..Default::default()
+            })
+            .collect(),
+        updatemail_instruction_list: list(value, "updatemail_instruction_list")
+            .map(|record| UpdatemailInstruction {
+                trx_hash: str_field(record, "trx_hash"),
+                body: str_field(record, "body"),
+                acct_mail: str_field(record, "acct_mail"),
+                mail_id: str_field(record, "mail_id"),
+                ..Default::default()
+            })
+            .collect(),
+        sendmail_instruction_list: list(value, "sendmail_instruction_list")
+            .map(|record| SendmailInstruction {
+                trx_hash: str_field(record, "trx_hash"),
+                subject: str_field(record, "subject"),
+                body: str_field(record, "body"),
+                from: str_field(record, "from"),
+                to: str_field(record, "to"),
+                salt: str_field(record, "salt"),
+                iv: str_field(record, "iv"),
+                version: str_field(record, "version"),
+                acct_mail: str_field(record, "acct_mail"),
+                mail_id: str_field(record, "mail_id"),
+                ..Default::default()
+            })
+            .collect(),
+        mail_v2_update_event_event_list: list(value, "mail_v2_update_event_event_list")
+            .map(|record| MailV2UpdateEventEvent {
+                trx_hash: str_field(record, "trx_hash"),
+                from: str_field(record, "from"),
+                to: str_field(record, "to"),
+                id: str_field(record, "id"),
+                parent_id: str_field(record, "parent_id"),
+                subject: str_field(record, "subject"),
+                body: str_field(record, "body"),
+                iv: str_field(record, "iv"),
+                salt: str_field(record, "salt"),
+                version: str_field(record, "version"),
+                created_at: u64_field(record, "created_at") as u32,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Field by its proto name or protojson camelCase name
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value.get(name).or_else(|| value.get(camel_case(name)))
+}
+
+fn list<'a>(value: &'a Value, name: &str) -> impl Iterator<Item = &'a Value> {
+    field(value, name)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn str_field(value: &Value, name: &str) -> String {
+    field(value, name)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn u64_field(value: &Value, name: &str) -> u64 {
+    match field(value, name) {
+        Some(Value::Number(number)) => number.as_u64().unwrap_or_default(),
+        Some(Value::String(number)) => number.parse().unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jsonl() {
+        let text = r#"
+{"@module":"map_program_data","@block":300000000,"@data":{"slot":"300000000","blockTime":"1700000000","sendmailInstructionList":[{"trxHash":"tx","subject":"hi","from":"alice","to":"bob","acctMail":"account","mailId":"mail"}]}}
+{"slot":300000001,"block_time":1700000400,"updatemail_instruction_list":[{"body":"cipher","acct_mail":"account","mail_id":"mail"}]}
+"#;
+        let data = parse_jsonl(text).unwrap();
+        assert_eq!(data.len(), 2);
+
+        assert_eq!(data[0].slot, 300000000);
+        assert_eq!(data[0].block_time, 1700000000);
+        assert_eq!(data[0].sendmail_instruction_list[0].acct_mail, "account");
+        assert_eq!(data[0].sendmail_instruction_list[0].mail_id, "mail");
+        assert_eq!(data[0].sendmail_instruction_list[0].trx_hash, "tx");
+
+        assert_eq!(data[1].block_time, 1700000400);
+        assert_eq!(data[1].updatemail_instruction_list[0].body, "cipher");
+        assert_eq!(data[1].updatemail_instruction_list[0].mail_id, "mail");
+    }
+
+    #[test]
+    fn test_parse_jsonl_reports_line() {
+        let err = parse_jsonl("{}\nnot json").unwrap_err();
+        assert!(err.message.starts_with("line 2:"), "{}", err);
+    }
+}
@@ -0,0 +1,20 @@
+//! Archive Solmail mailboxes in formats ordinary mail clients can import.
+//!
+//! Decoded `map_program_data` outputs (binary `Data` messages or JSONL) are
+//! folded into one message per mail, then rendered as RFC 5322 `.eml` files
+//! or a single mbox. Bodies are kept encrypted; the salt, IV and version
+//! needed to decrypt them travel as `X-Solmail-*` headers.
+
+pub mod error;
+pub mod input;
+pub mod mailbox;
+pub mod rfc5322;
+
+/// Protobuf types generated from `proto/program.proto` by the indexer build
+#[allow(dead_code, clippy::all)]
+pub mod pb {
+    include!("../../src/pb/substreams.v1.program.rs");
+}
+
+pub use error::ExportError;
+pub use mailbox::{Archive, Message};
@@ -0,0 +1,213 @@
+//! Folding of Solmail records into mails.
+//!
+//! A mail is identified by its mail id: the `id` of the events, which
+//! instructions carry as `mail_id` and replies as `parent_id`. `createmail`
+//! opens it without a body, `updatemail` sets the body, `sendmail` carries it
+//! whole and `MailV2UpdateEvent` holds its latest state. The date of a mail is
+//! the block time at which it first appeared.
+//!
+//! Instructions whose invocation logged no event have no mail id and cannot be
+//! told apart from other mails, so they are skipped.
+
+use std::collections::HashMap;
+
+use crate::pb::Data;
+
+/// A mail rebuilt from its records, body still encrypted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub parent_id: String,
+    pub salt: String,
+    pub iv: String,
+    pub version: String,
+    pub trx_hash: String,
+    pub slot: u64,
+    pub block_time: i64,
+}
+
+/// Mails of every mailbox seen in the input, in order of first appearance
+#[derive(Debug, Default)]
+pub struct Archive {
+    messages: Vec<Message>,
+    positions: HashMap<String, usize>,
+    /// Bodies set before the mail they belong to was seen
+    pending_bodies: HashMap<String, String>,
+}
+
+impl Archive {
+    pub fn new() -> Self {
+        Archive::default()
+    }
+
+    /// Fold the records of one block output, in the order the indexer lists
+    /// them: mails are opened before their bodies are updated
+    pub fn add_data(&mut self, data: &Data) {
+        for instruction in data.createmail_instruction_list.iter().filter(|i| !i.mail_id.is_empty()) {
+            let message = self.entry(&instruction.mail_id, data);
+            message.from = instruction.from.clone();
+            message.to = instruction.to.clone();
+            message.subject = instruction.subject.clone();
+            message.parent_id = instruction.parent_id.clone();
+            message.salt = instruction.salt.clone();
+            message.iv = instruction.iv.clone();
+            message.version = instruction.version.clone();
+            message.trx_hash = instruction.trx_hash.clone();
+        }
+
+        for instruction in data.sendmail_instruction_list.iter().filter(|i| !i.mail_id.is_empty()) {
+            let message = self.entry(&instruction.mail_id, data);
+            message.from = instruction.from.clone();
+            message.to = instruction.to.clone();
+            message.subject = instruction.subject.clone();
+            message.body = instruction.body.clone();
+            message.salt = instruction.salt.clone();
+            message.iv = instruction.iv.clone();
+            message.version = instruction.version.clone();
+            message.trx_hash = instruction.trx_hash.clone();
+        }
+
+        for instruction in data.updatemail_instruction_list.iter().filter(|i| !i.mail_id.is_empty()) {
+            match self.positions.get(&instruction.mail_id) {
+                Some(&position) => self.messages[position].body = instruction.body.clone(),
+                None => {
+                    self.pending_bodies.insert(instruction.mail_id.clone(), instruction.body.clone());
+                }
+            }
+        }
+
+        for event in data.mail_v2_update_event_event_list.iter().filter(|e| !e.id.is_empty()) {
+            let message = self.entry(&event.id, data);
+            message.from = event.from.clone();
+            message.to = event.to.clone();
+            message.subject = event.subject.clone();
+            message.parent_id = event.parent_id.clone();
+            message.salt = event.salt.clone();
+            message.iv = event.iv.clone();
+            message.version = event.version.clone();
+            if !event.body.is_empty() {
+                message.body = event.body.clone();
+            }
+            if message.trx_hash.is_empty() {
+                message.trx_hash = event.trx_hash.clone();
+            }
+        }
+    }
+
+    /// Mails sent or received by `mailbox`, in order of first appearance
+    pub fn mailbox(&self, mailbox: &str) -> Vec<&Message> {
+        self.messages
+            .iter()
+            .filter(|message| message.from == mailbox || message.to == mailbox)
+            .collect()
+    }
+
+    fn entry(&mut self, id: &str, data: &Data) -> &mut Message {
+        let position = match self.positions.get(id) {
+            Some(&position) => position,
+            None => {
+                self.messages.push(Message {
+                    id: id.to_string(),
+                    body: self.pending_bodies.remove(id).unwrap_or_default(),
+                    slot: data.slot,
+                    block_time: data.block_time,
+                    ..Default::default()
+                });
+                self.positions.insert(id.to_string(), self.messages.len() - 1);
+                self.messages.len() - 1
+            }
+        };
+        &mut self.messages[position]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::{CreatemailInstruction, MailV2UpdateEventEvent, SendmailInstruction, UpdatemailInstruction};
+
+    #[test]
+    fn test_createmail_then_updatemail() {
+        let mut archive = Archive::new();
+        archive.add_data(&Data {
+            slot: 1,
+            block_time: 100,
+            createmail_instruction_list: vec![CreatemailInstruction {
+                acct_mail: "account1".to_string(),
+                mail_id: "mail1".to_string(),
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                subject: "hi".to_string(),
+                ..Default::default()
+            }],
+            sendmail_instruction_list: vec![SendmailInstruction {
+                acct_mail: "account2".to_string(),
+                mail_id: "mail2".to_string(),
+                from: "carol".to_string(),
+                to: "dave".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        archive.add_data(&Data {
+            slot: 2,
+            block_time: 200,
+            updatemail_instruction_list: vec![UpdatemailInstruction {
+                acct_mail: "account1".to_string(),
+                mail_id: "mail1".to_string(),
+                body: "cipher".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        let messages = archive.mailbox("bob");
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].id, "mail1");
+        assert_eq!(messages[0].body, "cipher");
+        assert_eq!(messages[0].block_time, 100);
+
+        assert!(archive.mailbox("erin").is_empty());
+    }
+
+    #[test]
+    fn test_createmail_with_its_event() {
+        let mut archive = Archive::new();
+        archive.add_data(&Data {
+            slot: 1,
+            block_time: 100,
+            createmail_instruction_list: vec![CreatemailInstruction {
+                acct_mail: "account1".to_string(),
+                mail_id: "mail1".to_string(),
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                subject: "re: hi".to_string(),
+                parent_id: "mail0".to_string(),
+                trx_hash: "tx1".to_string(),
+                ..Default::default()
+            }],
+            mail_v2_update_event_event_list: vec![MailV2UpdateEventEvent {
+                id: "mail1".to_string(),
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                subject: "re: hi".to_string(),
+                parent_id: "mail0".to_string(),
+                body: "cipher".to_string(),
+                trx_hash: "tx1".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        let messages = archive.mailbox("alice");
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].id, "mail1");
+        assert_eq!(messages[0].parent_id, "mail0");
+        assert_eq!(messages[0].body, "cipher");
+        assert_eq!(messages[0].trx_hash, "tx1");
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use solmail_export::{input, rfc5322, Archive, ExportError};
+
+const USAGE: &str = "\
+Usage: solmail-export --mailbox <PUBKEY> [--format mbox|eml] [--output <PATH>] <FILE>...
+
+Export the mails of a Solmail mailbox from decoded map_program_data outputs
+(.jsonl/.json lines, or binary Data messages).
+
+Options:
+  --mailbox <PUBKEY>  Pubkey whose sent and received mails are exported
+  --format <FORMAT>   mbox (default) or eml
+  --output <PATH>     mbox file (default: stdout) or eml directory (default: .)";
+
+enum Format {
+    Mbox,
+    Eml,
+}
+
+struct Args {
+    mailbox: String,
+    format: Format,
+    output: Option<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `None` when help was asked for
+fn parse_args(mut raw: impl Iterator<Item = String>) -> Result<Option<Args>, ExportError> {
+    let mut mailbox = None;
+    let mut format = Format::Mbox;
+    let mut output = None;
+    let mut files = Vec::new();
+
+    while let Some(arg) = raw.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--mailbox" => mailbox = Some(raw.next().ok_or("--mailbox needs a value")?),
+            "--format" => {
+                format = match raw.next().as_deref() {
+                    Some("mbox") => Format::Mbox,
+                    Some("eml") => Format::Eml,
+                    other => return Err(format!("unknown format {:?}", other.unwrap_or_default()).into()),
+                }
+            }
+            "--output" => output = Some(PathBuf::from(raw.next().ok_or("--output needs a value")?)),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag).into()),
+            file => files.push(PathBuf::from(file)),
+        }
+    }
+
+    let mailbox = mailbox.ok_or("--mailbox is required")?;
+    if files.is_empty() {
+        return Err("no input file".into());
+    }
+
+    Ok(Some(Args { mailbox, format, output, files }))
+}
+
+fn run(args: Args) -> Result<(), ExportError> {
+    let mut archive = Archive::new();
+    for file in &args.files {
+        for data in input::read_file(file)? {
+            archive.add_data(&data);
+        }
+    }
+
+    let messages = archive.mailbox(&args.mailbox);
+    eprintln!("{} mails for {}", messages.len(), args.mailbox);
+
+    match args.format {
+        Format::Mbox => {
+            let mbox = rfc5322::to_mbox(messages);
+            match args.output {
+                Some(path) => fs::write(path, mbox)?,
+                None => std::io::stdout().write_all(mbox.as_bytes())?,
+            }
+        }
+        Format::Eml => {
+            let directory = args.output.unwrap_or_else(|| PathBuf::from("."));
+            fs::create_dir_all(&directory)?;
+            let mut written = BTreeSet::new();
+            for message in messages {
+                // Ids differing only by replaced characters share a name
+                let mut name = rfc5322::eml_file_name(message);
+                while !written.insert(name.clone()) {
+                    name = format!("{}-{}", written.len(), name);
+                }
+                fs::write(directory.join(name), rfc5322::to_eml(message))?;
+            }
+        }
+    }
+
+    Ok(())
+}
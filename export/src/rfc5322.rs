@@ -0,0 +1,182 @@
+//! RFC 5322 rendering of mails, as `.eml` files or an mbox.
+//!
+//! Pubkeys become addresses in the `solmail` domain (`<pubkey>@solmail`) and
+//! mail ids become `Message-ID`s, so threads resolve through `In-Reply-To`
+//! and `References`.
+
+use base64::prelude::*;
+use chrono::DateTime;
+
+use crate::mailbox::Message;
+
+/// Domain of the addresses and message ids derived from pubkeys
+pub const ADDRESS_DOMAIN: &str = "solmail";
+
+/// A mail as an `.eml` file, with CRLF line endings
+pub fn to_eml(message: &Message) -> String {
+    let mut eml = String::new();
+    for (name, value) in headers(message) {
+        eml.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    eml.push_str("\r\n");
+    for line in message.body.lines() {
+        eml.push_str(line);
+        eml.push_str("\r\n");
+    }
+    eml
+}
+
+/// File name of a mail's `.eml`, `<slot>-<mail id>.eml`.
+///
+/// Mail ids are chosen by whoever sends the mail, so anything but ASCII
+/// letters, digits, `-` and `_` is replaced with `_` and the id is cut to
+/// [MAX_FILE_ID_LEN] characters; the file cannot leave the output directory.
+pub fn eml_file_name(message: &Message) -> String {
+    let id: String = message
+        .id
+        .chars()
+        .take(MAX_FILE_ID_LEN)
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}-{}.eml", message.slot, id)
+}
+
+/// Longest mail id kept in an `.eml` file name
+pub const MAX_FILE_ID_LEN: usize = 128;
+
+/// Mails as an mboxrd file, with LF line endings
+pub fn to_mbox<'a>(messages: impl IntoIterator<Item = &'a Message>) -> String {
+    let mut mbox = String::new();
+    for message in messages {
+        let date = DateTime::from_timestamp(message.block_time, 0).unwrap_or_default();
+        mbox.push_str(&format!("From {} {}\n", address(&message.from), date.format("%a %b %e %H:%M:%S %Y")));
+
+        for (name, value) in headers(message) {
+            mbox.push_str(&format!("{}: {}\n", name, value));
+        }
+        mbox.push('\n');
+
+        for line in message.body.lines() {
+            // mboxrd: quote lines that could be read as a message separator
+            if line.trim_start_matches('>').starts_with("From ") {
+                mbox.push('>');
+            }
+            mbox.push_str(line);
+            mbox.push('\n');
+        }
+        mbox.push('\n');
+    }
+    mbox
+}
+
+fn headers(message: &Message) -> Vec<(&'static str, String)> {
+    let date = DateTime::from_timestamp(message.block_time, 0).unwrap_or_default();
+
+    let mut headers = vec![
+        ("From", address(&message.from)),
+        ("To", address(&message.to)),
+        ("Subject", encode_header(&message.subject)),
+        ("Date", date.to_rfc2822()),
+        ("Message-ID", message_id(&message.id)),
+    ];
+    if !message.parent_id.is_empty() {
+        headers.push(("In-Reply-To", message_id(&message.parent_id)));
+        headers.push(("References", message_id(&message.parent_id)));
+    }
+    headers.extend([
+        ("MIME-Version", "1.0".to_string()),
+        ("Content-Type", "text/plain; charset=utf-8".to_string()),
+        ("Content-Transfer-Encoding", "8bit".to_string()),
+        ("X-Solmail-Salt", sanitize(&message.salt)),
+        ("X-Solmail-Iv", sanitize(&message.iv)),
+        ("X-Solmail-Version", sanitize(&message.version)),
+        ("X-Solmail-Transaction", sanitize(&message.trx_hash)),
+        ("X-Solmail-Slot", message.slot.to_string()),
+    ]);
+    headers
+}
+
+fn address(pubkey: &str) -> String {
+    format!("{}@{}", sanitize(pubkey), ADDRESS_DOMAIN)
+}
+
+fn message_id(id: &str) -> String {
+    format!("<{}@{}>", sanitize(id), ADDRESS_DOMAIN)
+}
+
+/// Header value as is when printable ASCII, RFC 2047 encoded otherwise
+fn encode_header(value: &str) -> String {
+    let value = sanitize(value);
+    if value.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        value
+    } else {
+        format!("=?UTF-8?B?{}?=", BASE64_STANDARD.encode(value.as_bytes()))
+    }
+}
+
+/// Line breaks would end the header early
+fn sanitize(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> Message {
+        Message {
+            id: "mail2".to_string(),
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            subject: "hi".to_string(),
+            body: "cipher\nFrom here".to_string(),
+            parent_id: "mail1".to_string(),
+            salt: "salt".to_string(),
+            iv: "iv".to_string(),
+            version: "2".to_string(),
+            trx_hash: "tx".to_string(),
+            slot: 7,
+            block_time: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_to_eml() {
+        let eml = to_eml(&message());
+        assert!(eml.starts_with("From: alice@solmail\r\nTo: bob@solmail\r\nSubject: hi\r\n"));
+        assert!(eml.contains("Date: Tue, 14 Nov 2023 22:13:20 +0000\r\n"));
+        assert!(eml.contains("Message-ID: <mail2@solmail>\r\n"));
+        assert!(eml.contains("In-Reply-To: <mail1@solmail>\r\nReferences: <mail1@solmail>\r\n"));
+        assert!(eml.contains("X-Solmail-Iv: iv\r\n"));
+        assert!(eml.ends_with("\r\n\r\ncipher\r\nFrom here\r\n"));
+    }
+
+    #[test]
+    fn test_to_mbox() {
+        let mbox = to_mbox([&message()]);
+        assert!(mbox.starts_with("From alice@solmail Tue Nov 14 22:13:20 2023\n"));
+        assert!(mbox.ends_with("\n\ncipher\n>From here\n\n"));
+    }
+
+    #[test]
+    fn test_eml_file_name() {
+        assert_eq!(eml_file_name(&message()), "7-mail2.eml");
+
+        let mut hostile = message();
+        hostile.id = "../../etc/passwd".to_string();
+        assert_eq!(eml_file_name(&hostile), "7-______etc_passwd.eml");
+
+        hostile.id = "a\\b/c.eml".to_string();
+        assert_eq!(eml_file_name(&hostile), "7-a_b_c_eml.eml");
+
+        hostile.id = "x".repeat(1_000);
+        assert_eq!(eml_file_name(&hostile).len(), "7-.eml".len() + MAX_FILE_ID_LEN);
+    }
+
+    #[test]
+    fn test_encode_header() {
+        assert_eq!(encode_header("hello"), "hello");
+        assert_eq!(encode_header("héllo"), "=?UTF-8?B?aMOpbGxv?=");
+        assert_eq!(encode_header("a\r\nb"), "a  b");
+    }
+}
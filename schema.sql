@@ -87,9 +87,14 @@ CREATE TABLE IF NOT EXISTS createmail_instruction (
     version TEXT NOT NULL,
     parent_id TEXT NOT NULL,
     acct_mail TEXT NOT NULL,
+    mail_id TEXT NOT NULL DEFAULT '',
     acct_mail_account_v2 TEXT NOT NULL,
     acct_authority TEXT NOT NULL,
     acct_system_program TEXT NOT NULL,
+    fee_payer TEXT NOT NULL DEFAULT '',
+    sponsored BOOLEAN NOT NULL DEFAULT false,
+    compute_units_consumed BIGINT NOT NULL DEFAULT 0,
+    violations TEXT NOT NULL DEFAULT '',
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -99,8 +104,13 @@ CREATE TABLE IF NOT EXISTS updatemail_instruction (
     trx_hash VARCHAR(88) NOT NULL,
     body TEXT NOT NULL,
     acct_mail TEXT NOT NULL,
+    mail_id TEXT NOT NULL DEFAULT '',
     acct_authority TEXT NOT NULL,
     acct_system_program TEXT NOT NULL,
+    fee_payer TEXT NOT NULL DEFAULT '',
+    sponsored BOOLEAN NOT NULL DEFAULT false,
+    compute_units_consumed BIGINT NOT NULL DEFAULT 0,
+    violations TEXT NOT NULL DEFAULT '',
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -109,8 +119,12 @@ CREATE TABLE IF NOT EXISTS updatemailreadstatus_instruction (
     id VARCHAR(64) PRIMARY KEY,
     trx_hash VARCHAR(88) NOT NULL,
     acct_mail TEXT NOT NULL,
+    mail_id TEXT NOT NULL DEFAULT '',
     acct_authority TEXT NOT NULL,
     acct_system_program TEXT NOT NULL,
+    fee_payer TEXT NOT NULL DEFAULT '',
+    sponsored BOOLEAN NOT NULL DEFAULT false,
+    compute_units_consumed BIGINT NOT NULL DEFAULT 0,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -120,8 +134,13 @@ CREATE TABLE IF NOT EXISTS updatemaillabel_instruction (
     trx_hash VARCHAR(88) NOT NULL,
     label BIGINT NOT NULL,
     acct_mail TEXT NOT NULL,
+    mail_id TEXT NOT NULL DEFAULT '',
     acct_authority TEXT NOT NULL,
     acct_system_program TEXT NOT NULL,
+    fee_payer TEXT NOT NULL DEFAULT '',
+    sponsored BOOLEAN NOT NULL DEFAULT false,
+    compute_units_consumed BIGINT NOT NULL DEFAULT 0,
+    violations TEXT NOT NULL DEFAULT '',
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -130,9 +149,14 @@ CREATE TABLE IF NOT EXISTS register_v2_instruction (
     id VARCHAR(64) PRIMARY KEY,
     trx_hash VARCHAR(88) NOT NULL,
     nostr_key TEXT NOT NULL,
+    nostr_key_kind VARCHAR(8) NOT NULL DEFAULT '',
+    nostr_key_hex VARCHAR(64) NOT NULL DEFAULT '',
+    nostr_npub VARCHAR(63) NOT NULL DEFAULT '',
     acct_mail_account_v2 TEXT NOT NULL,
     acct_authority TEXT NOT NULL,
     acct_system_program TEXT NOT NULL,
+    compute_units_consumed BIGINT NOT NULL DEFAULT 0,
+    violations TEXT NOT NULL DEFAULT '',
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -141,9 +165,14 @@ CREATE TABLE IF NOT EXISTS update_account_v2_instruction (
     id VARCHAR(64) PRIMARY KEY,
     trx_hash VARCHAR(88) NOT NULL,
     nostr_key TEXT NOT NULL,
+    nostr_key_kind VARCHAR(8) NOT NULL DEFAULT '',
+    nostr_key_hex VARCHAR(64) NOT NULL DEFAULT '',
+    nostr_npub VARCHAR(63) NOT NULL DEFAULT '',
     mailbox TEXT NOT NULL,
     acct_mail_account_v2 TEXT NOT NULL,
     acct_authority TEXT NOT NULL,
+    compute_units_consumed BIGINT NOT NULL DEFAULT 0,
+    violations TEXT NOT NULL DEFAULT '',
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -159,8 +188,13 @@ CREATE TABLE IF NOT EXISTS sendmail_instruction (
     iv TEXT NOT NULL,
     version TEXT NOT NULL,
     acct_mail TEXT NOT NULL,
+    mail_id TEXT NOT NULL DEFAULT '',
     acct_authority TEXT NOT NULL,
     acct_system_program TEXT NOT NULL,
+    fee_payer TEXT NOT NULL DEFAULT '',
+    sponsored BOOLEAN NOT NULL DEFAULT false,
+    compute_units_consumed BIGINT NOT NULL DEFAULT 0,
+    violations TEXT NOT NULL DEFAULT '',
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -169,12 +203,301 @@ CREATE TABLE IF NOT EXISTS register_instruction (
     id VARCHAR(64) PRIMARY KEY,
     trx_hash VARCHAR(88) NOT NULL,
     nostr_key TEXT NOT NULL,
+    nostr_key_kind VARCHAR(8) NOT NULL DEFAULT '',
+    nostr_key_hex VARCHAR(64) NOT NULL DEFAULT '',
+    nostr_npub VARCHAR(63) NOT NULL DEFAULT '',
     acct_mail_account TEXT NOT NULL,
     acct_authority TEXT NOT NULL,
     acct_system_program TEXT NOT NULL,
+    compute_units_consumed BIGINT NOT NULL DEFAULT 0,
+    violations TEXT NOT NULL DEFAULT '',
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
+-- Transaction Meta Table (one row per Solmail transaction)
+-- fee_payer/sponsored columns are only on the createmail, updatemail,
+-- updatemailreadstatus, updatemaillabel and sendmail instruction tables;
+-- every other row gets its payer by joining here on trx_hash
+CREATE TABLE IF NOT EXISTS transaction_meta (
+    id VARCHAR(64) PRIMARY KEY,
+    trx_hash VARCHAR(88) NOT NULL,
+    slot BIGINT NOT NULL,
+    fee_payer TEXT NOT NULL,
+    signers TEXT NOT NULL,
+    num_signers INTEGER NOT NULL,
+    fee BIGINT NOT NULL,
+    compute_units_consumed BIGINT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Sponsored Sends Per Sponsor Per Day
+CREATE TABLE IF NOT EXISTS sponsor_stats (
+    day DATE NOT NULL,
+    sponsor TEXT NOT NULL,
+    sponsored_sends BIGINT NOT NULL,
+    PRIMARY KEY (day, sponsor)
+);
+
+-- Daily Compute Units Per Instruction Kind
+CREATE TABLE IF NOT EXISTS daily_compute_units (
+    day DATE NOT NULL,
+    kind TEXT NOT NULL,
+    invocations BIGINT NOT NULL,
+    avg_compute_units BIGINT NOT NULL,
+    p50_compute_units BIGINT NOT NULL,
+    p90_compute_units BIGINT NOT NULL,
+    p99_compute_units BIGINT NOT NULL,
+    PRIMARY KEY (day, kind)
+);
+
+-- Program Log Table (written by the db_out_program_logs sink module)
+CREATE TABLE IF NOT EXISTS program_log (
+    id VARCHAR(64) PRIMARY KEY,
+    trx_hash VARCHAR(88) NOT NULL,
+    slot BIGINT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    invocation_index INTEGER NOT NULL,
+    depth INTEGER NOT NULL,
+    ordinal INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    failed BOOLEAN NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Program Error Table (failed Solmail transactions)
+CREATE TABLE IF NOT EXISTS program_error (
+    id VARCHAR(64) PRIMARY KEY,
+    trx_hash VARCHAR(88) NOT NULL,
+    slot BIGINT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    instruction_index INTEGER NOT NULL,
+    instruction TEXT NOT NULL,
+    code BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    message TEXT NOT NULL,
+    signer TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Daily Failures Per Instruction And Error Code
+CREATE TABLE IF NOT EXISTS error_stats (
+    day DATE NOT NULL,
+    instruction TEXT NOT NULL,
+    code BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    failures BIGINT NOT NULL,
+    PRIMARY KEY (day, instruction, code)
+);
+
+-- Daily Mails Per Encryption Version And Client Cohort
+CREATE TABLE IF NOT EXISTS mail_version_stats (
+    day DATE NOT NULL,
+    version TEXT NOT NULL,
+    mails BIGINT NOT NULL DEFAULT 0,
+    unknown_version BIGINT NOT NULL DEFAULT 0,
+    mismatched_lengths BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (day, version)
+);
+
+-- Daily Network Statistics
+CREATE TABLE IF NOT EXISTS daily_stats (
+    day DATE PRIMARY KEY,
+    mails_sent BIGINT NOT NULL DEFAULT 0,
+    reads BIGINT NOT NULL DEFAULT 0,
+    label_changes BIGINT NOT NULL DEFAULT 0,
+    registrations BIGINT NOT NULL DEFAULT 0,
+    active_senders BIGINT NOT NULL DEFAULT 0,
+    active_recipients BIGINT NOT NULL DEFAULT 0,
+    active_users BIGINT NOT NULL DEFAULT 0,
+    unique_pairs BIGINT NOT NULL DEFAULT 0
+);
+
+-- Per-User Counters
+CREATE TABLE IF NOT EXISTS user_stats (
+    address TEXT PRIMARY KEY,
+    sent BIGINT NOT NULL DEFAULT 0,
+    received BIGINT NOT NULL DEFAULT 0,
+    read BIGINT NOT NULL DEFAULT 0,
+    unread BIGINT NOT NULL DEFAULT 0,
+    trashed BIGINT NOT NULL DEFAULT 0,
+    spam BIGINT NOT NULL DEFAULT 0,
+    first_seen_slot BIGINT NOT NULL DEFAULT 0,
+    last_active_slot BIGINT NOT NULL DEFAULT 0
+);
+
+-- Sender To Recipient Interaction Edges
+CREATE TABLE IF NOT EXISTS interaction_edge (
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    messages BIGINT NOT NULL DEFAULT 0,
+    replies BIGINT NOT NULL DEFAULT 0,
+    first_message_slot BIGINT NOT NULL DEFAULT 0,
+    last_message_slot BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (from_address, to_address)
+);
+
+-- Undirected conversation pairs, keyed by the two pubkeys in canonical order
+CREATE OR REPLACE VIEW conversation_pair AS
+SELECT
+    LEAST(from_address, to_address) AS address_a,
+    GREATEST(from_address, to_address) AS address_b,
+    SUM(messages) AS messages,
+    SUM(replies) AS replies,
+    MIN(first_message_slot) AS first_message_slot,
+    MAX(last_message_slot) AS last_message_slot
+FROM interaction_edge
+GROUP BY LEAST(from_address, to_address), GREATEST(from_address, to_address);
+
+-- Time From Send To First Read Per Mail
+CREATE TABLE IF NOT EXISTS mail_read_latency (
+    mail_id TEXT PRIMARY KEY,
+    reader TEXT NOT NULL,
+    trx_hash VARCHAR(88) NOT NULL,
+    sent_slot BIGINT NOT NULL,
+    sent_at BIGINT NOT NULL,
+    read_slot BIGINT NOT NULL,
+    read_at BIGINT NOT NULL,
+    latency_seconds BIGINT NOT NULL
+);
+
+-- Daily Time-To-Read Percentiles
+CREATE TABLE IF NOT EXISTS daily_read_latency (
+    day DATE PRIMARY KEY,
+    reads BIGINT NOT NULL,
+    avg_latency_seconds BIGINT NOT NULL,
+    p50_latency_seconds BIGINT NOT NULL,
+    p90_latency_seconds BIGINT NOT NULL,
+    p99_latency_seconds BIGINT NOT NULL
+);
+
+-- Reply Response Times Per Replier, Overall (Empty recipient) And Per Recipient
+CREATE TABLE IF NOT EXISTS user_response_time (
+    address TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    replies BIGINT NOT NULL,
+    avg_response_seconds BIGINT NOT NULL,
+    p50_response_seconds BIGINT NOT NULL,
+    p90_response_seconds BIGINT NOT NULL,
+    p99_response_seconds BIGINT NOT NULL,
+    PRIMARY KEY (address, recipient)
+);
+
+-- Mail Label Transitions
+CREATE TABLE IF NOT EXISTS mail_label_history (
+    id VARCHAR(64) PRIMARY KEY,
+    trx_hash VARCHAR(88) NOT NULL,
+    slot BIGINT NOT NULL,
+    mail_id TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    previous_label BIGINT,
+    label BIGINT NOT NULL
+);
+
+-- Sender Spam Signals And Reputation
+CREATE TABLE IF NOT EXISTS sender_reputation (
+    address TEXT PRIMARY KEY,
+    sent BIGINT NOT NULL,
+    spam_labels BIGINT NOT NULL,
+    spam_reports BIGINT NOT NULL,
+    reporters BIGINT NOT NULL,
+    score NUMERIC(5, 4) NOT NULL
+);
+
+-- Burst Alerts On High-Volume Senders
+CREATE TABLE IF NOT EXISTS abuse_alert (
+    id VARCHAR(64) PRIMARY KEY,
+    sender TEXT NOT NULL,
+    trx_hash VARCHAR(88) NOT NULL,
+    slot BIGINT NOT NULL,
+    window_start_slot BIGINT NOT NULL,
+    sends BIGINT NOT NULL,
+    recipients BIGINT NOT NULL,
+    unregistered_recipients BIGINT NOT NULL,
+    unregistered_pct INTEGER NOT NULL,
+    reasons TEXT NOT NULL
+);
+
+-- V2 Mailboxes
+CREATE TABLE IF NOT EXISTS mailbox (
+    mailbox TEXT PRIMARY KEY,
+    authority TEXT NOT NULL DEFAULT '',
+    authority_since_slot BIGINT NOT NULL DEFAULT 0,
+    messages BIGINT NOT NULL DEFAULT 0,
+    read BIGINT NOT NULL DEFAULT 0,
+    unread BIGINT NOT NULL DEFAULT 0
+);
+
+-- V2 Mailbox Authority Changes
+CREATE TABLE IF NOT EXISTS mailbox_ownership_history (
+    id VARCHAR(64) PRIMARY KEY,
+    trx_hash VARCHAR(88) NOT NULL,
+    slot BIGINT NOT NULL,
+    mailbox TEXT NOT NULL,
+    previous_authority TEXT,
+    authority TEXT NOT NULL
+);
+
+-- Unified Activity Feed Table
+CREATE TABLE IF NOT EXISTS activity (
+    id VARCHAR(64) PRIMARY KEY,
+    slot BIGINT NOT NULL,
+    block_time BIGINT NOT NULL,
+    trx_hash VARCHAR(88) NOT NULL,
+    tx_index INTEGER NOT NULL,
+    ordinal INTEGER NOT NULL,
+    invocation_index INTEGER NOT NULL,
+    signer TEXT NOT NULL,
+    program_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    from_address TEXT,
+    to_address TEXT,
+    owner TEXT,
+    authority TEXT,
+    mailbox TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Column migrations for tables created by earlier versions
+ALTER TABLE createmail_instruction ADD COLUMN IF NOT EXISTS fee_payer TEXT NOT NULL DEFAULT '';
+ALTER TABLE createmail_instruction ADD COLUMN IF NOT EXISTS sponsored BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE updatemail_instruction ADD COLUMN IF NOT EXISTS fee_payer TEXT NOT NULL DEFAULT '';
+ALTER TABLE updatemail_instruction ADD COLUMN IF NOT EXISTS sponsored BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE updatemailreadstatus_instruction ADD COLUMN IF NOT EXISTS fee_payer TEXT NOT NULL DEFAULT '';
+ALTER TABLE updatemailreadstatus_instruction ADD COLUMN IF NOT EXISTS sponsored BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE updatemaillabel_instruction ADD COLUMN IF NOT EXISTS fee_payer TEXT NOT NULL DEFAULT '';
+ALTER TABLE updatemaillabel_instruction ADD COLUMN IF NOT EXISTS sponsored BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE sendmail_instruction ADD COLUMN IF NOT EXISTS fee_payer TEXT NOT NULL DEFAULT '';
+ALTER TABLE sendmail_instruction ADD COLUMN IF NOT EXISTS sponsored BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE createmail_instruction ADD COLUMN IF NOT EXISTS compute_units_consumed BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE updatemail_instruction ADD COLUMN IF NOT EXISTS compute_units_consumed BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE updatemailreadstatus_instruction ADD COLUMN IF NOT EXISTS compute_units_consumed BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE updatemaillabel_instruction ADD COLUMN IF NOT EXISTS compute_units_consumed BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE register_v2_instruction ADD COLUMN IF NOT EXISTS compute_units_consumed BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE update_account_v2_instruction ADD COLUMN IF NOT EXISTS compute_units_consumed BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sendmail_instruction ADD COLUMN IF NOT EXISTS compute_units_consumed BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE register_instruction ADD COLUMN IF NOT EXISTS compute_units_consumed BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE createmail_instruction ADD COLUMN IF NOT EXISTS violations TEXT NOT NULL DEFAULT '';
+ALTER TABLE updatemail_instruction ADD COLUMN IF NOT EXISTS violations TEXT NOT NULL DEFAULT '';
+ALTER TABLE register_v2_instruction ADD COLUMN IF NOT EXISTS violations TEXT NOT NULL DEFAULT '';
+ALTER TABLE update_account_v2_instruction ADD COLUMN IF NOT EXISTS violations TEXT NOT NULL DEFAULT '';
+ALTER TABLE sendmail_instruction ADD COLUMN IF NOT EXISTS violations TEXT NOT NULL DEFAULT '';
+ALTER TABLE register_instruction ADD COLUMN IF NOT EXISTS violations TEXT NOT NULL DEFAULT '';
+ALTER TABLE updatemaillabel_instruction ADD COLUMN IF NOT EXISTS violations TEXT NOT NULL DEFAULT '';
+ALTER TABLE register_v2_instruction ADD COLUMN IF NOT EXISTS nostr_key_kind VARCHAR(8) NOT NULL DEFAULT '';
+ALTER TABLE register_v2_instruction ADD COLUMN IF NOT EXISTS nostr_key_hex VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE register_v2_instruction ADD COLUMN IF NOT EXISTS nostr_npub VARCHAR(63) NOT NULL DEFAULT '';
+ALTER TABLE update_account_v2_instruction ADD COLUMN IF NOT EXISTS nostr_key_kind VARCHAR(8) NOT NULL DEFAULT '';
+ALTER TABLE update_account_v2_instruction ADD COLUMN IF NOT EXISTS nostr_key_hex VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE update_account_v2_instruction ADD COLUMN IF NOT EXISTS nostr_npub VARCHAR(63) NOT NULL DEFAULT '';
+ALTER TABLE register_instruction ADD COLUMN IF NOT EXISTS nostr_key_kind VARCHAR(8) NOT NULL DEFAULT '';
+ALTER TABLE register_instruction ADD COLUMN IF NOT EXISTS nostr_key_hex VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE register_instruction ADD COLUMN IF NOT EXISTS nostr_npub VARCHAR(63) NOT NULL DEFAULT '';
+ALTER TABLE createmail_instruction ADD COLUMN IF NOT EXISTS mail_id TEXT NOT NULL DEFAULT '';
+ALTER TABLE updatemail_instruction ADD COLUMN IF NOT EXISTS mail_id TEXT NOT NULL DEFAULT '';
+ALTER TABLE updatemailreadstatus_instruction ADD COLUMN IF NOT EXISTS mail_id TEXT NOT NULL DEFAULT '';
+ALTER TABLE updatemaillabel_instruction ADD COLUMN IF NOT EXISTS mail_id TEXT NOT NULL DEFAULT '';
+ALTER TABLE sendmail_instruction ADD COLUMN IF NOT EXISTS mail_id TEXT NOT NULL DEFAULT '';
+
 -- Create indexes for performance
 -- Transaction hash indexes
 CREATE INDEX IF NOT EXISTS idx_mail_send_event_trx_hash ON mail_send_event(trx_hash);
@@ -199,6 +522,11 @@ CREATE INDEX IF NOT EXISTS idx_mail_v2_send_event_mail_id ON mail_v2_send_event(
 CREATE INDEX IF NOT EXISTS idx_mail_v2_update_event_mail_id ON mail_v2_update_event(mail_id);
 CREATE INDEX IF NOT EXISTS idx_mail_v2_read_event_mail_id ON mail_v2_read_event(mail_id);
 CREATE INDEX IF NOT EXISTS idx_mail_v2_update_label_event_mail_id ON mail_v2_update_label_event(mail_id);
+CREATE INDEX IF NOT EXISTS idx_createmail_instruction_mail_id ON createmail_instruction(mail_id);
+CREATE INDEX IF NOT EXISTS idx_updatemail_instruction_mail_id ON updatemail_instruction(mail_id);
+CREATE INDEX IF NOT EXISTS idx_updatemailreadstatus_instruction_mail_id ON updatemailreadstatus_instruction(mail_id);
+CREATE INDEX IF NOT EXISTS idx_updatemaillabel_instruction_mail_id ON updatemaillabel_instruction(mail_id);
+CREATE INDEX IF NOT EXISTS idx_sendmail_instruction_mail_id ON sendmail_instruction(mail_id);
 
 -- Owner/Account indexes
 CREATE INDEX IF NOT EXISTS idx_mail_v2_read_event_owner ON mail_v2_read_event(owner);
@@ -224,6 +552,11 @@ CREATE INDEX IF NOT EXISTS idx_createmail_instruction_to ON createmail_instructi
 CREATE INDEX IF NOT EXISTS idx_sendmail_instruction_from ON sendmail_instruction(from_address);
 CREATE INDEX IF NOT EXISTS idx_sendmail_instruction_to ON sendmail_instruction(to_address);
 
+-- Nostr key indexes for account instructions
+CREATE INDEX IF NOT EXISTS idx_register_instruction_nostr_npub ON register_instruction(nostr_npub);
+CREATE INDEX IF NOT EXISTS idx_register_v2_instruction_nostr_npub ON register_v2_instruction(nostr_npub);
+CREATE INDEX IF NOT EXISTS idx_update_account_v2_instruction_nostr_npub ON update_account_v2_instruction(nostr_npub);
+
 -- Timestamp indexes for all tables
 CREATE INDEX IF NOT EXISTS idx_mail_send_event_created_at ON mail_send_event(created_at DESC);
 CREATE INDEX IF NOT EXISTS idx_mail_v2_send_event_created_at ON mail_v2_send_event(created_at DESC);
@@ -238,6 +571,60 @@ CREATE INDEX IF NOT EXISTS idx_mail_v2_update_event_from_to_time ON mail_v2_upda
 CREATE INDEX IF NOT EXISTS idx_mail_v2_send_event_mailbox_time ON mail_v2_send_event(mailbox, created_at DESC);
 CREATE INDEX IF NOT EXISTS idx_mail_v2_update_event_read_status ON mail_v2_update_event(mark_as_read, created_at DESC);
 
+-- Sponsored mail indexes
+CREATE INDEX IF NOT EXISTS idx_createmail_instruction_fee_payer ON createmail_instruction(fee_payer) WHERE sponsored;
+CREATE INDEX IF NOT EXISTS idx_sendmail_instruction_fee_payer ON sendmail_instruction(fee_payer) WHERE sponsored;
+CREATE INDEX IF NOT EXISTS idx_sponsor_stats_sponsor ON sponsor_stats(sponsor, day DESC);
+
+-- Transaction meta indexes
+CREATE INDEX IF NOT EXISTS idx_transaction_meta_trx_hash ON transaction_meta(trx_hash);
+CREATE INDEX IF NOT EXISTS idx_transaction_meta_fee_payer ON transaction_meta(fee_payer);
+
+-- Program log indexes
+CREATE INDEX IF NOT EXISTS idx_program_log_trx_hash ON program_log(trx_hash);
+
+-- Program error indexes
+CREATE INDEX IF NOT EXISTS idx_program_error_trx_hash ON program_error(trx_hash);
+CREATE INDEX IF NOT EXISTS idx_program_error_signer ON program_error(signer);
+CREATE INDEX IF NOT EXISTS idx_program_error_name ON program_error(name);
+
+-- User stats indexes
+CREATE INDEX IF NOT EXISTS idx_user_stats_last_active ON user_stats(last_active_slot DESC);
+
+-- Interaction edge indexes
+CREATE INDEX IF NOT EXISTS idx_interaction_edge_to ON interaction_edge(to_address);
+CREATE INDEX IF NOT EXISTS idx_interaction_edge_messages ON interaction_edge(messages DESC);
+
+-- Read latency indexes
+CREATE INDEX IF NOT EXISTS idx_mail_read_latency_reader ON mail_read_latency(reader);
+
+-- Response time indexes
+CREATE INDEX IF NOT EXISTS idx_user_response_time_recipient ON user_response_time(recipient);
+
+-- Label history indexes
+CREATE INDEX IF NOT EXISTS idx_mail_label_history_mail ON mail_label_history(mail_id, slot);
+CREATE INDEX IF NOT EXISTS idx_mail_label_history_owner ON mail_label_history(owner);
+
+-- Sender reputation indexes
+CREATE INDEX IF NOT EXISTS idx_sender_reputation_score ON sender_reputation(score);
+
+-- Abuse alert indexes
+CREATE INDEX IF NOT EXISTS idx_abuse_alert_sender ON abuse_alert(sender, slot);
+CREATE INDEX IF NOT EXISTS idx_abuse_alert_slot ON abuse_alert(slot);
+
+-- Mailbox indexes
+CREATE INDEX IF NOT EXISTS idx_mailbox_authority ON mailbox(authority);
+CREATE INDEX IF NOT EXISTS idx_mailbox_ownership_history_mailbox ON mailbox_ownership_history(mailbox, slot);
+CREATE INDEX IF NOT EXISTS idx_mailbox_ownership_history_authority ON mailbox_ownership_history(authority);
+
+-- Activity feed indexes
+CREATE INDEX IF NOT EXISTS idx_activity_order ON activity(slot DESC, tx_index DESC, ordinal DESC);
+CREATE INDEX IF NOT EXISTS idx_activity_trx_hash ON activity(trx_hash);
+CREATE INDEX IF NOT EXISTS idx_activity_kind ON activity(kind);
+CREATE INDEX IF NOT EXISTS idx_activity_from ON activity(from_address);
+CREATE INDEX IF NOT EXISTS idx_activity_to ON activity(to_address);
+CREATE INDEX IF NOT EXISTS idx_activity_owner ON activity(owner);
+
 -- Add table comments for documentation
 COMMENT ON TABLE mail_send_event IS 'Mail send events from the Solana mail protocol';
 COMMENT ON TABLE mail_v2_send_event IS 'Mail V2 send events with mailbox support';
@@ -253,4 +640,24 @@ COMMENT ON TABLE updatemaillabel_instruction IS 'Update mail label instruction d
 COMMENT ON TABLE register_v2_instruction IS 'Register V2 instruction data';
 COMMENT ON TABLE update_account_v2_instruction IS 'Update account V2 instruction data';
 COMMENT ON TABLE sendmail_instruction IS 'Send mail instruction data';
-COMMENT ON TABLE register_instruction IS 'Register instruction data';
\ No newline at end of file
+COMMENT ON TABLE register_instruction IS 'Register instruction data';
+COMMENT ON TABLE transaction_meta IS 'Fee payer, signers (comma separated), fee and compute units of each Solmail transaction; join on trx_hash for the payer of rows without a fee_payer column';
+COMMENT ON TABLE sponsor_stats IS 'Daily sponsored sends (fee payer differs from authority) per sponsor';
+COMMENT ON TABLE daily_compute_units IS 'Daily compute units per instruction kind, percentiles approximated in 2000 CU buckets';
+COMMENT ON TABLE program_log IS 'msg! log lines of Solmail invocations, per invocation and line ordinal';
+COMMENT ON TABLE program_error IS 'Decoded Anchor errors of failed Solmail transactions';
+COMMENT ON TABLE error_stats IS 'Daily failed Solmail transactions per instruction and error code';
+COMMENT ON TABLE mail_version_stats IS 'Daily new mails per encryption version; unknown_version counts mails of a version without known salt/IV lengths, mismatched_lengths mails of a known version with other lengths';
+COMMENT ON TABLE daily_stats IS 'Daily mails sent, reads, label changes, registrations and distinct active participants (UTC days)';
+COMMENT ON TABLE user_stats IS 'Per-address mail counters with first seen and last active slots';
+COMMENT ON TABLE interaction_edge IS 'Mails, replies and first/last message slots per sender and recipient';
+COMMENT ON VIEW conversation_pair IS 'Interaction edges merged per unordered pair of pubkeys';
+COMMENT ON TABLE mail_read_latency IS 'Seconds from send to first read per mail';
+COMMENT ON TABLE daily_read_latency IS 'Daily time-to-read of first reads, percentiles approximated in quarter-octave buckets';
+COMMENT ON TABLE user_response_time IS 'Seconds from a mail to its reply per replier, overall when recipient is empty and per recipient otherwise';
+COMMENT ON TABLE mail_label_history IS 'Label transitions per mail (0 outbox, 1 inbox, 2 read, 3 trash, 4 spam), previous_label is NULL for the first label seen';
+COMMENT ON TABLE sender_reputation IS 'Spam labels received per sender; score is the smoothed share of mails not labelled as spam, from 0 to 1';
+COMMENT ON TABLE abuse_alert IS 'Senders crossing a map_abuse_alerts threshold over the current and previous 750-slot window; reasons lists the crossed thresholds';
+COMMENT ON TABLE mailbox IS 'V2 mailboxes with their current authority (from updateAccountV2) and message counts (from MailV2SendEvent)';
+COMMENT ON TABLE mailbox_ownership_history IS 'Authority changes of V2 mailboxes, previous_authority is NULL for the first authority seen';
+COMMENT ON TABLE activity IS 'Unified feed of all events and instructions in block order';
\ No newline at end of file
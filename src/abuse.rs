@@ -0,0 +1,160 @@
+//! Burst detection for senders spraying mail at many recipients.
+//!
+//! Sends are counted per sender in windows of [WINDOW_SLOTS] slots and
+//! evaluated over the current and previous window, a span that slides by one
+//! window at a time. Thresholds come from the `map_abuse_alerts` params.
+
+use substreams::errors::Error;
+
+/// Slots per counting window, about 5 minutes
+pub const WINDOW_SLOTS: u64 = 750;
+
+/// Counting window of a slot
+pub fn window(slot: u64) -> u64 {
+    slot / WINDOW_SLOTS
+}
+
+/// Sends of a sender over the evaluated span
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WindowCounts {
+    pub sends: i64,
+    /// Distinct recipients per window, summed over the span: a recipient
+    /// mailed in both windows counts twice
+    pub recipients: i64,
+    /// Recipients without a registered mail account when first mailed
+    pub unregistered: i64,
+}
+
+impl WindowCounts {
+    /// Share of unregistered recipients, in percent
+    pub fn unregistered_pct(&self) -> u32 {
+        if self.recipients <= 0 {
+            return 0;
+        }
+        (self.unregistered.max(0) * 100 / self.recipients) as u32
+    }
+}
+
+impl std::ops::Sub for WindowCounts {
+    type Output = WindowCounts;
+
+    fn sub(self, other: WindowCounts) -> WindowCounts {
+        WindowCounts {
+            sends: self.sends - other.sends,
+            recipients: self.recipients - other.recipients,
+            unregistered: self.unregistered - other.unregistered,
+        }
+    }
+}
+
+/// Limits above which a sender is reported
+#[derive(Debug, PartialEq)]
+pub struct AbuseThresholds {
+    /// Mails sent over the span
+    pub max_sends: i64,
+    /// Recipients over the span, distinct per window (see
+    /// [WindowCounts::recipients])
+    pub max_recipients: i64,
+    /// Percentage of unregistered recipients, once `min_recipients` is reached
+    pub max_unregistered_pct: u32,
+    pub min_recipients: i64,
+}
+
+impl Default for AbuseThresholds {
+    fn default() -> Self {
+        AbuseThresholds {
+            max_sends: 1_000,
+            max_recipients: 500,
+            max_unregistered_pct: 80,
+            min_recipients: 50,
+        }
+    }
+}
+
+impl AbuseThresholds {
+    /// Parse `key=value` pairs separated by `&`, e.g. `max_sends=200`.
+    /// Missing keys keep their default.
+    pub fn parse(params: &str) -> Result<Self, Error> {
+        let mut parsed = AbuseThresholds::default();
+
+        for pair in params.split('&').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| Error::msg(format!("invalid abuse param {:?}, expected key=value", pair)))?;
+            let number = value
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| Error::msg(format!("invalid value {:?} for abuse param {:?}", value, name)))?;
+
+            match name.trim() {
+                "max_sends" => parsed.max_sends = number as i64,
+                "max_recipients" => parsed.max_recipients = number as i64,
+                "max_unregistered_pct" => parsed.max_unregistered_pct = number,
+                "min_recipients" => parsed.min_recipients = number as i64,
+                _ => return Err(Error::msg(format!("unknown abuse param {:?}", name))),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Thresholds exceeded by `counts`
+    pub fn exceeded(&self, counts: &WindowCounts) -> Vec<&'static str> {
+        let mut reasons = Vec::new();
+        if counts.sends > self.max_sends {
+            reasons.push("sends");
+        }
+        if counts.recipients > self.max_recipients {
+            reasons.push("recipients");
+        }
+        if counts.recipients >= self.min_recipients && counts.unregistered_pct() > self.max_unregistered_pct {
+            reasons.push("unregistered_recipients");
+        }
+        reasons
+    }
+
+    /// Thresholds crossed from `before` to `after`, so that a sender is
+    /// reported once per threshold while a burst lasts
+    pub fn crossed(&self, before: &WindowCounts, after: &WindowCounts) -> Vec<&'static str> {
+        let already = self.exceeded(before);
+        self.exceeded(after)
+            .into_iter()
+            .filter(|reason| !already.contains(reason))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_abuse_thresholds() {
+        assert_eq!(AbuseThresholds::parse("").unwrap(), AbuseThresholds::default());
+
+        let thresholds = AbuseThresholds::parse("max_sends=10&min_recipients=2").unwrap();
+        assert_eq!(thresholds.max_sends, 10);
+        assert_eq!(thresholds.min_recipients, 2);
+        assert_eq!(thresholds.max_recipients, 500);
+
+        assert!(AbuseThresholds::parse("max_sends").is_err());
+        assert!(AbuseThresholds::parse("max_sends=-1").is_err());
+        assert!(AbuseThresholds::parse("unknown=1").is_err());
+    }
+
+    #[test]
+    fn test_crossed_thresholds() {
+        let thresholds = AbuseThresholds::parse("max_sends=10&max_recipients=5&max_unregistered_pct=50&min_recipients=4").unwrap();
+        let before = WindowCounts { sends: 10, recipients: 6, unregistered: 1 };
+        let after = WindowCounts { sends: 12, recipients: 8, unregistered: 5 };
+
+        assert_eq!(thresholds.exceeded(&after), vec!["sends", "recipients", "unregistered_recipients"]);
+        assert_eq!(thresholds.crossed(&before, &after), vec!["sends", "unregistered_recipients"]);
+        assert!(thresholds.crossed(&after, &after).is_empty());
+
+        // Too few recipients for the percentage to matter
+        let few = WindowCounts { sends: 3, recipients: 3, unregistered: 3 };
+        assert_eq!(few.unregistered_pct(), 100);
+        assert!(thresholds.exceeded(&few).is_empty());
+    }
+}
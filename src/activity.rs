@@ -0,0 +1,215 @@
+use std::collections::BTreeSet;
+
+use crate::pb::substreams::v1::program::activity_item::Item;
+use crate::pb::substreams::v1::program::Data;
+
+impl Item {
+    /// Name of the event or instruction, as written in the IDL
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Item::MailSendEvent(_) => "MailSendEvent",
+            Item::MailV2SendEvent(_) => "MailV2SendEvent",
+            Item::MailV2UpdateEvent(_) => "MailV2UpdateEvent",
+            Item::MailV2ReadEvent(_) => "MailV2ReadEvent",
+            Item::MailV2UpdateLabelEvent(_) => "MailV2UpdateLabelEvent",
+            Item::MailAccountV2RegisterEvent(_) => "MailAccountV2RegisterEvent",
+            Item::MailAccountV2UpdateEvent(_) => "MailAccountV2UpdateEvent",
+            Item::CreatemailInstruction(_) => "createmail",
+            Item::UpdatemailInstruction(_) => "updatemail",
+            Item::UpdatemailreadstatusInstruction(_) => "updatemailreadstatus",
+            Item::UpdatemaillabelInstruction(_) => "updatemaillabel",
+            Item::RegisterV2Instruction(_) => "registerV2",
+            Item::UpdateAccountV2Instruction(_) => "updateAccountV2",
+            Item::SendmailInstruction(_) => "sendmail",
+            Item::RegisterInstruction(_) => "register",
+        }
+    }
+
+    /// Addresses referenced by the item along with the role they play in it.
+    ///
+    /// Roles are `from`, `to`, `owner`, `authority` and `mailbox`; empty
+    /// addresses (missing accounts) are skipped.
+    pub fn addresses(&self) -> Vec<(&'static str, &str)> {
+        let addresses: Vec<(&'static str, &str)> = match self {
+            Item::MailSendEvent(event) => vec![("from", &event.from), ("to", &event.to)],
+            Item::MailV2SendEvent(event) => vec![
+                ("from", &event.from),
+                ("to", &event.to),
+                ("mailbox", &event.mailbox),
+            ],
+            Item::MailV2UpdateEvent(event) => vec![
+                ("from", &event.from),
+                ("to", &event.to),
+                ("mailbox", &event.mailbox),
+                ("authority", &event.authority),
+            ],
+            Item::MailV2ReadEvent(event) => vec![("owner", &event.owner)],
+            Item::MailV2UpdateLabelEvent(event) => vec![("owner", &event.owner)],
+            Item::MailAccountV2RegisterEvent(event) => vec![("owner", &event.owner)],
+            Item::MailAccountV2UpdateEvent(event) => vec![("owner", &event.owner)],
+            Item::CreatemailInstruction(instruction) => vec![
+                ("from", &instruction.from),
+                ("to", &instruction.to),
+                ("authority", &instruction.acct_authority),
+            ],
+            Item::UpdatemailInstruction(instruction) => vec![("authority", &instruction.acct_authority)],
+            Item::UpdatemailreadstatusInstruction(instruction) => vec![("authority", &instruction.acct_authority)],
+            Item::UpdatemaillabelInstruction(instruction) => vec![("authority", &instruction.acct_authority)],
+            Item::RegisterV2Instruction(instruction) => vec![("authority", &instruction.acct_authority)],
+            Item::UpdateAccountV2Instruction(instruction) => vec![
+                ("authority", &instruction.acct_authority),
+                ("mailbox", &instruction.mailbox),
+            ],
+            Item::SendmailInstruction(instruction) => vec![
+                ("from", &instruction.from),
+                ("to", &instruction.to),
+                ("authority", &instruction.acct_authority),
+            ],
+            Item::RegisterInstruction(instruction) => vec![("authority", &instruction.acct_authority)],
+        };
+
+        addresses
+            .into_iter()
+            .filter(|(_, address)| !address.is_empty())
+            .collect()
+    }
+
+    /// Compute units consumed by the invocation, for instructions only
+    pub fn compute_units_consumed(&self) -> Option<u64> {
+        match self {
+            Item::CreatemailInstruction(instruction) => Some(instruction.compute_units_consumed),
+            Item::UpdatemailInstruction(instruction) => Some(instruction.compute_units_consumed),
+            Item::UpdatemailreadstatusInstruction(instruction) => Some(instruction.compute_units_consumed),
+            Item::UpdatemaillabelInstruction(instruction) => Some(instruction.compute_units_consumed),
+            Item::RegisterV2Instruction(instruction) => Some(instruction.compute_units_consumed),
+            Item::UpdateAccountV2Instruction(instruction) => Some(instruction.compute_units_consumed),
+            Item::SendmailInstruction(instruction) => Some(instruction.compute_units_consumed),
+            Item::RegisterInstruction(instruction) => Some(instruction.compute_units_consumed),
+            _ => None,
+        }
+    }
+
+    /// Mutable access to the compute units of an instruction
+    pub fn compute_units_consumed_mut(&mut self) -> Option<&mut u64> {
+        match self {
+            Item::CreatemailInstruction(instruction) => Some(&mut instruction.compute_units_consumed),
+            Item::UpdatemailInstruction(instruction) => Some(&mut instruction.compute_units_consumed),
+            Item::UpdatemailreadstatusInstruction(instruction) => Some(&mut instruction.compute_units_consumed),
+            Item::UpdatemaillabelInstruction(instruction) => Some(&mut instruction.compute_units_consumed),
+            Item::RegisterV2Instruction(instruction) => Some(&mut instruction.compute_units_consumed),
+            Item::UpdateAccountV2Instruction(instruction) => Some(&mut instruction.compute_units_consumed),
+            Item::SendmailInstruction(instruction) => Some(&mut instruction.compute_units_consumed),
+            Item::RegisterInstruction(instruction) => Some(&mut instruction.compute_units_consumed),
+            _ => None,
+        }
+    }
+
+    /// Id of the mail an event is about, shared with the `parent_id` of
+    /// replies
+    pub fn mail_id(&self) -> Option<&str> {
+        match self {
+            Item::MailSendEvent(event) => Some(event.id.as_str()),
+            Item::MailV2SendEvent(event) => Some(event.id.as_str()),
+            Item::MailV2UpdateEvent(event) => Some(event.id.as_str()),
+            Item::MailV2ReadEvent(event) => Some(event.id.as_str()),
+            Item::MailV2UpdateLabelEvent(event) => Some(event.id.as_str()),
+            _ => None,
+        }
+        .filter(|id| !id.is_empty())
+    }
+
+    /// Mutable access to the mail id of a mail instruction, which is only
+    /// known from the events of its invocation
+    pub fn mail_id_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Item::CreatemailInstruction(instruction) => Some(&mut instruction.mail_id),
+            Item::UpdatemailInstruction(instruction) => Some(&mut instruction.mail_id),
+            Item::UpdatemailreadstatusInstruction(instruction) => Some(&mut instruction.mail_id),
+            Item::UpdatemaillabelInstruction(instruction) => Some(&mut instruction.mail_id),
+            Item::SendmailInstruction(instruction) => Some(&mut instruction.mail_id),
+            _ => None,
+        }
+    }
+
+    /// Whether any of the given addresses takes part in the item
+    pub fn involves(&self, addresses: &[String]) -> bool {
+        self.addresses()
+            .iter()
+            .any(|(_, address)| addresses.iter().any(|wanted| wanted == address))
+    }
+}
+
+impl Data {
+    /// Sender, recipient and transaction of every new mail (`createmail` and
+    /// `sendmail`)
+    pub fn mail_sends(&self) -> impl Iterator<Item = (&str, &str, &str)> {
+        self.createmail_instruction_list.iter().map(|i| (i.from.as_str(), i.to.as_str(), i.trx_hash.as_str()))
+            .chain(self.sendmail_instruction_list.iter().map(|i| (i.from.as_str(), i.to.as_str(), i.trx_hash.as_str())))
+    }
+
+    /// Sender and recipient of every new mail (`createmail` and `sendmail`)
+    pub fn mail_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.mail_sends().map(|(from, to, _)| (from, to))
+    }
+
+    /// Mail, reader and transaction of every read (`MailV2ReadEvent` and
+    /// `updatemailreadstatus`)
+    pub fn mail_reads(&self) -> Vec<(&str, &str, &str)> {
+        self.mail_v2_read_event_event_list.iter().map(|e| (e.id.as_str(), e.owner.as_str(), e.trx_hash.as_str()))
+            .chain(self.updatemailreadstatus_instruction_list.iter().map(|i| (i.mail_id.as_str(), i.acct_authority.as_str(), i.trx_hash.as_str())))
+            .filter(|(mail, _, _)| !mail.is_empty())
+            .collect()
+    }
+
+    /// Addresses taking part in the block's instructions, in any role
+    pub fn instruction_addresses(&self) -> BTreeSet<&str> {
+        let mails = self.createmail_instruction_list.iter().map(|i| [&i.from, &i.to])
+            .chain(self.sendmail_instruction_list.iter().map(|i| [&i.from, &i.to]))
+            .flatten();
+        let mailboxes = self.update_account_v2_instruction_list.iter().map(|i| &i.mailbox);
+
+        mails
+            .chain(mailboxes)
+            .map(String::as_str)
+            .chain(self.instruction_authorities())
+            .filter(|address| !address.is_empty())
+            .collect()
+    }
+
+    /// Authorities (signers) of the block's instructions
+    pub fn instruction_authorities(&self) -> BTreeSet<&str> {
+        self.createmail_instruction_list.iter().map(|i| &i.acct_authority)
+            .chain(self.updatemail_instruction_list.iter().map(|i| &i.acct_authority))
+            .chain(self.updatemailreadstatus_instruction_list.iter().map(|i| &i.acct_authority))
+            .chain(self.updatemaillabel_instruction_list.iter().map(|i| &i.acct_authority))
+            .chain(self.register_v2_instruction_list.iter().map(|i| &i.acct_authority))
+            .chain(self.update_account_v2_instruction_list.iter().map(|i| &i.acct_authority))
+            .chain(self.sendmail_instruction_list.iter().map(|i| &i.acct_authority))
+            .chain(self.register_instruction_list.iter().map(|i| &i.acct_authority))
+            .map(String::as_str)
+            .filter(|address| !address.is_empty())
+            .collect()
+    }
+
+    /// Every event and instruction of the block output, list by list
+    pub fn into_items(self) -> impl Iterator<Item = Item> {
+        let events = self.mail_send_event_event_list.into_iter().map(Item::MailSendEvent)
+            .chain(self.mail_v2_send_event_event_list.into_iter().map(Item::MailV2SendEvent))
+            .chain(self.mail_v2_update_event_event_list.into_iter().map(Item::MailV2UpdateEvent))
+            .chain(self.mail_v2_read_event_event_list.into_iter().map(Item::MailV2ReadEvent))
+            .chain(self.mail_v2_update_label_event_event_list.into_iter().map(Item::MailV2UpdateLabelEvent))
+            .chain(self.mail_account_v2_register_event_event_list.into_iter().map(Item::MailAccountV2RegisterEvent))
+            .chain(self.mail_account_v2_update_event_event_list.into_iter().map(Item::MailAccountV2UpdateEvent));
+
+        let instructions = self.createmail_instruction_list.into_iter().map(Item::CreatemailInstruction)
+            .chain(self.updatemail_instruction_list.into_iter().map(Item::UpdatemailInstruction))
+            .chain(self.updatemailreadstatus_instruction_list.into_iter().map(Item::UpdatemailreadstatusInstruction))
+            .chain(self.updatemaillabel_instruction_list.into_iter().map(Item::UpdatemaillabelInstruction))
+            .chain(self.register_v2_instruction_list.into_iter().map(Item::RegisterV2Instruction))
+            .chain(self.update_account_v2_instruction_list.into_iter().map(Item::UpdateAccountV2Instruction))
+            .chain(self.sendmail_instruction_list.into_iter().map(Item::SendmailInstruction))
+            .chain(self.register_instruction_list.into_iter().map(Item::RegisterInstruction));
+
+        events.chain(instructions)
+    }
+}
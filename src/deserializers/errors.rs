@@ -0,0 +1,89 @@
+use crate::deserializers::helpers::*;
+
+/// `TransactionError::InstructionError` variant index in the bincode encoding
+const INSTRUCTION_ERROR: u32 = 8;
+/// `InstructionError::Custom` variant index in the bincode encoding
+const CUSTOM_ERROR: u32 = 25;
+
+/// Error raised by an instruction of a failed transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstructionErrorCustom {
+    pub instruction_index: u8,
+    /// Custom error code, when the instruction failed with `InstructionError::Custom`
+    pub custom_code: Option<u32>,
+}
+
+/// Anchor error parsed from an `AnchorError ... Error Code: ...` program log
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnchorErrorLog {
+    pub name: String,
+    pub code: u32,
+    pub message: String,
+}
+
+/// Deserialize the bincode encoded `meta.err` of a failed transaction.
+///
+/// Only `InstructionError` is of interest here, any other transaction error
+/// yields `None`.
+pub fn deserialize_instruction_error(data: &[u8]) -> Option<InstructionErrorCustom> {
+    let mut offset = 0;
+
+    if parse_u32(data, &mut offset).ok()? != INSTRUCTION_ERROR {
+        return None;
+    }
+    let instruction_index = *data.get(offset)?;
+    offset += 1;
+
+    let custom_code = match parse_u32(data, &mut offset) {
+        Ok(CUSTOM_ERROR) => parse_u32(data, &mut offset).ok(),
+        _ => None,
+    };
+
+    Some(InstructionErrorCustom { instruction_index, custom_code })
+}
+
+/// Parse the log Anchor writes when an instruction fails, e.g.
+/// `AnchorError occurred. Error Code: InvalidBody. Error Number: 6001. Error Message: The body ... chars.`
+pub fn parse_anchor_error_log(log: &str) -> Option<AnchorErrorLog> {
+    let (_, rest) = log.split_once("Error Code: ")?;
+    let (name, rest) = rest.split_once(". Error Number: ")?;
+    let (code, rest) = rest.split_once(". Error Message: ")?;
+
+    Some(AnchorErrorLog {
+        name: name.to_string(),
+        code: code.trim().parse().ok()?,
+        message: rest.trim_end_matches('.').to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_instruction_error() {
+        // InstructionError(1, Custom(6001))
+        let data = vec![8u8, 0, 0, 0, 1, 25, 0, 0, 0, 0x71, 0x17, 0, 0];
+        let error = deserialize_instruction_error(&data).unwrap();
+        assert_eq!(error.instruction_index, 1);
+        assert_eq!(error.custom_code, Some(6001));
+
+        // InstructionError(0, MissingRequiredSignature)
+        let data = vec![8u8, 0, 0, 0, 0, 7, 0, 0, 0];
+        assert_eq!(deserialize_instruction_error(&data).unwrap().custom_code, None);
+
+        // InsufficientFundsForFee
+        assert_eq!(deserialize_instruction_error(&[4u8, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_parse_anchor_error_log() {
+        let log = "AnchorError thrown in programs/solmail/src/lib.rs:42. Error Code: InvalidSubject. Error Number: 6002. Error Message: The subject of your email is too long. The max is 40 chars.";
+        let error = parse_anchor_error_log(log).unwrap();
+        assert_eq!(error.name, "InvalidSubject");
+        assert_eq!(error.code, 6002);
+        assert_eq!(error.message, "The subject of your email is too long. The max is 40 chars");
+
+        assert_eq!(parse_anchor_error_log("Instruction: Sendmail"), None);
+    }
+}
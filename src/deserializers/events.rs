@@ -102,7 +102,6 @@ pub fn deserialize_mail_account_v2_update_event(data: &[u8]) -> Result<MailAccou
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anchor_lang::prelude::Pubkey;
 
     #[test]
     fn test_deserialize_mail_send_event() {
@@ -90,6 +90,18 @@ pub fn parse_pubkey(data: &[u8], offset: &mut usize) -> Result<Pubkey, Deseriali
     Ok(pubkey)
 }
 
+/// Parse a u8 from binary data
+pub fn parse_u8(data: &[u8], offset: &mut usize) -> Result<u8, DeserializationError> {
+    if data.len() < *offset + 1 {
+        return Err("Data too short for u8".into());
+    }
+
+    let value = data[*offset];
+    *offset += 1;
+
+    Ok(value)
+}
+
 /// Parse a u32 from binary data (little-endian)
 pub fn parse_u32(data: &[u8], offset: &mut usize) -> Result<u32, DeserializationError> {
     if data.len() < *offset + 4 {
@@ -120,6 +132,7 @@ pub fn parse_bool(data: &[u8], offset: &mut usize) -> Result<bool, Deserializati
 }
 
 /// Validate that we have enough data remaining
+#[allow(dead_code)]
 pub fn validate_remaining_bytes(data: &[u8], offset: usize, needed: usize, context: &str) -> Result<(), DeserializationError> {
     if data.len() < offset + needed {
         return Err(format!(
@@ -163,11 +176,11 @@ mod tests {
         let mut offset = 0;
         
         let result1 = parse_bool(&data, &mut offset).unwrap();
-        assert_eq!(result1, true);
+        assert!(result1);
         assert_eq!(offset, 1);
         
         let result2 = parse_bool(&data, &mut offset).unwrap();
-        assert_eq!(result2, false);
+        assert!(!result2);
         assert_eq!(offset, 2);
     }
 }
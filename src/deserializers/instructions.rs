@@ -41,7 +41,7 @@ pub fn deserialize_updatemailreadstatus_instruction(_data: &[u8]) -> Result<Upda
 /// Deserialize Updatemaillabel instruction from binary data
 pub fn deserialize_updatemaillabel_instruction(data: &[u8]) -> Result<UpdatemaillabelCustom, DeserializationError> {
     let mut offset = 0;
-    let label = parse_u32(data, &mut offset)?;
+    let label = parse_u8(data, &mut offset)?;
     
     Ok(UpdatemaillabelCustom { label })
 }
@@ -153,13 +153,13 @@ mod tests {
     #[test]
     fn test_deserialize_updatemailreadstatus_instruction() {
         let data = Vec::new(); // Empty data since this instruction has no fields
-        let result = deserialize_updatemailreadstatus_instruction(&data).unwrap();
+        deserialize_updatemailreadstatus_instruction(&data).unwrap();
         // Just ensure it doesn't panic
     }
 
     #[test]
     fn test_deserialize_updatemaillabel_instruction() {
-        let data = vec![2u8, 0, 0, 0]; // label = 2
+        let data = vec![2u8]; // label = 2 (u8 in the IDL)
         let result = deserialize_updatemaillabel_instruction(&data).unwrap();
         assert_eq!(result.label, 2);
     }
@@ -1,8 +1,9 @@
 pub mod helpers;
 pub mod events;
 pub mod instructions;
+pub mod errors;
 
 // Re-export all deserializers for easy access
 pub use events::*;
 pub use instructions::*;
-pub use helpers::*;
+pub use errors::*;
@@ -1,15 +1,54 @@
 use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+use substreams::errors::Error;
+use substreams::key;
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::pb::substreams::Clock;
+use substreams::store::{DeltaInt64, DeltaString, Deltas, StoreGet, StoreGetInt64, StoreGetString};
 use substreams::Hex;
 use substreams_database_change::pb::database::DatabaseChanges;
 use substreams_database_change::tables::Tables as DatabaseChangeTables;
 
-use crate::pb::substreams::v1::program::Data;
+use crate::pb::substreams::v1::program::{AbuseAlerts, Activity, Data, LabelChanges, MailReadLatencies, MailReplyTimes};
+use crate::handlers::helpers::day_from_clock;
 use crate::idl;
+use crate::stats::{sender_reputation, COMPUTE_UNITS_HISTOGRAM, READ_LATENCY_HISTOGRAM, RESPONSE_TIME_HISTOGRAM};
 
 /// Database output handler for converting parsed data to database changes
+///
+/// Options are passed as `key=value` pairs separated by `&` in the params, see
+/// [DbOutParams].
 #[substreams::handlers::map]
-pub fn db_out(data: Data) -> DatabaseChanges {
+pub fn db_out(
+    params: String,
+    clock: Clock,
+    data: Data,
+    activity: Activity,
+    sponsor_stats_deltas: Deltas<DeltaInt64>,
+    compute_units_store: StoreGetInt64,
+    error_stats_deltas: Deltas<DeltaInt64>,
+    version_stats_deltas: Deltas<DeltaInt64>,
+    daily_stats_deltas: Deltas<DeltaInt64>,
+    user_stats_deltas: Deltas<DeltaInt64>,
+    user_first_seen_deltas: Deltas<DeltaInt64>,
+    user_last_active_deltas: Deltas<DeltaInt64>,
+    interaction_edges_deltas: Deltas<DeltaInt64>,
+    interaction_first_slot_deltas: Deltas<DeltaInt64>,
+    interaction_last_slot_deltas: Deltas<DeltaInt64>,
+    read_latencies: MailReadLatencies,
+    read_latency_store: StoreGetInt64,
+    reply_times: MailReplyTimes,
+    response_time_store: StoreGetInt64,
+    label_changes: LabelChanges,
+    mail_senders_store: StoreGetString,
+    sender_reputation_store: StoreGetInt64,
+    abuse_alerts: AbuseAlerts,
+    mailbox_owners_deltas: Deltas<DeltaString>,
+    mailbox_stats_deltas: Deltas<DeltaInt64>,
+) -> Result<DatabaseChanges, Error> {
     substreams::log::info!("=== DATABASE OUTPUT PROCESSING ===");
+    let params = DbOutParams::parse(&params)?;
     
     let total_events = data.mail_send_event_event_list.len() + 
                       data.mail_v2_send_event_event_list.len() + 
@@ -34,17 +73,136 @@ pub fn db_out(data: Data) -> DatabaseChanges {
     let mut tables: DatabaseChangeTables = DatabaseChangeTables::new();
 
     // Process events
-    process_events(&data, &mut tables);
+    process_events(&data, params.content, &mut tables);
     
     // Process instructions
-    process_instructions(&data, &mut tables);
+    process_instructions(&data, params.content, &mut tables);
+
+    // Process transaction fee payers, signers and costs
+    process_transaction_metas(&data, &mut tables);
+
+    // Process the unified activity feed
+    process_activity(&activity, &mut tables);
+
+    // Process daily sponsored send counters
+    process_sponsor_stats(&sponsor_stats_deltas, &mut tables);
+
+    // Process daily compute unit percentiles
+    process_compute_units(&clock, &activity, &compute_units_store, &mut tables);
+
+    // Process failed transactions and their daily counters
+    process_program_errors(&data, &mut tables);
+    process_error_stats(&error_stats_deltas, &mut tables);
+
+    // Process daily mail counters per encryption version
+    process_version_stats(&version_stats_deltas, &mut tables);
+
+    // Process daily network counters
+    process_daily_stats(&daily_stats_deltas, &mut tables);
+
+    // Process per-user counters and activity slots
+    process_user_stats(&user_stats_deltas, &user_first_seen_deltas, &user_last_active_deltas, &mut tables);
+
+    // Process sender → recipient interaction edges
+    process_interaction_edges(
+        &interaction_edges_deltas,
+        &interaction_first_slot_deltas,
+        &interaction_last_slot_deltas,
+        &mut tables,
+    );
+
+    // Process time-to-read of first reads and its daily percentiles
+    process_read_latencies(&clock, &read_latencies, &read_latency_store, &mut tables);
+
+    // Process reply response time percentiles per replier and per pair
+    process_response_times(&reply_times, &response_time_store, &mut tables);
+
+    // Process label transitions with the label each change replaced
+    process_label_changes(&label_changes, &mut tables);
+
+    // Process the spam signals and reputation of senders active in this block
+    process_sender_reputation(&data, &mail_senders_store, &sender_reputation_store, &mut tables);
+
+    // Process burst alerts on high-volume senders
+    process_abuse_alerts(&abuse_alerts, &mut tables);
+
+    // Process V2 mailbox ownership and counters
+    process_mailboxes(&data, &mailbox_owners_deltas, &mailbox_stats_deltas, &mut tables);
 
     substreams::log::info!("=== DATABASE OUTPUT COMPLETE ===");
-    tables.to_database_changes()
+    Ok(tables.to_database_changes())
+}
+
+/// Options of the database output
+#[derive(Debug, Default, PartialEq)]
+struct DbOutParams {
+    /// How mail subjects, bodies, salts and IVs are written
+    content: ContentPolicy,
+}
+
+impl DbOutParams {
+    /// Parse `key=value` pairs separated by `&`, e.g. `content=hashed`
+    fn parse(params: &str) -> Result<Self, Error> {
+        let mut parsed = DbOutParams::default();
+
+        for pair in params.split('&').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| Error::msg(format!("invalid db_out param {:?}, expected key=value", pair)))?;
+
+            match name.trim() {
+                "content" => parsed.content = ContentPolicy::parse(value)?,
+                _ => return Err(Error::msg(format!("unknown db_out param {:?}", name))),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// What `db_out` keeps of mail content (subject, body, salt and IV) in
+/// `sendmail_instruction`, `createmail_instruction`, `updatemail_instruction`
+/// and `mail_v2_update_event`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum ContentPolicy {
+    /// Values as decoded
+    #[default]
+    Full,
+    /// Hex SHA-256 of each value
+    Hashed,
+    /// Length in bytes of each value
+    Length,
+    /// Empty values
+    Omitted,
+}
+
+impl ContentPolicy {
+    fn parse(value: &str) -> Result<Self, Error> {
+        match value.trim() {
+            "full" => Ok(ContentPolicy::Full),
+            "hashed" => Ok(ContentPolicy::Hashed),
+            "length" => Ok(ContentPolicy::Length),
+            "omitted" => Ok(ContentPolicy::Omitted),
+            _ => Err(Error::msg(format!(
+                "invalid value {:?} for db_out param \"content\", expected full, hashed, length or omitted",
+                value
+            ))),
+        }
+    }
+
+    /// Content value to write under this policy
+    fn apply(&self, value: &str) -> String {
+        match self {
+            ContentPolicy::Full => value.to_string(),
+            ContentPolicy::Hashed => Hex::encode(Sha256::digest(value.as_bytes())),
+            ContentPolicy::Length => value.len().to_string(),
+            ContentPolicy::Omitted => String::new(),
+        }
+    }
 }
 
 /// Process all event types and add them to database tables
-fn process_events(data: &Data, tables: &mut DatabaseChangeTables) {
+fn process_events(data: &Data, content: ContentPolicy, tables: &mut DatabaseChangeTables) {
     // Process MailSendEvent events
     for event in &data.mail_send_event_event_list {
         substreams::log::debug!("Creating DB row for MailSendEvent: {}", event.id);
@@ -108,13 +266,13 @@ fn process_events(data: &Data, tables: &mut DatabaseChangeTables) {
             .set("mail_id", &event.id)
             .set("mailbox", &event.mailbox)
             .set("parent_id", &event.parent_id)
-            .set("mark_as_read", &event.mark_as_read.to_string())
-            .set("created_at_timestamp", &event.created_at.to_string())
-            .set("subject", &event.subject)
-            .set("body", &event.body)
+            .set("mark_as_read", event.mark_as_read.to_string())
+            .set("created_at_timestamp", event.created_at.to_string())
+            .set("subject", content.apply(&event.subject))
+            .set("body", content.apply(&event.body))
             .set("authority", &event.authority)
-            .set("iv", &event.iv)
-            .set("salt", &event.salt)
+            .set("iv", content.apply(&event.iv))
+            .set("salt", content.apply(&event.salt))
             .set("version", &event.version);
     }
 
@@ -188,10 +346,10 @@ fn process_events(data: &Data, tables: &mut DatabaseChangeTables) {
 }
 
 /// Process all instruction types and add them to database tables
-fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
+fn process_instructions(data: &Data, content: ContentPolicy, tables: &mut DatabaseChangeTables) {
     // Process Createmail instructions
     for instruction in &data.createmail_instruction_list {
-        substreams::log::debug!("Creating DB row for Createmail instruction: subject={}", instruction.subject);
+        substreams::log::debug!("Creating DB row for Createmail instruction: {}", instruction.acct_mail);
         
         let pk = generate_instruction_pk(&[
             &instruction.trx_hash,
@@ -207,17 +365,22 @@ fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
         tables
             .create_row("createmail_instruction", pk)
             .set("trx_hash", &instruction.trx_hash)
-            .set("subject", &instruction.subject)
+            .set("subject", content.apply(&instruction.subject))
             .set("from_address", &instruction.from)
             .set("to_address", &instruction.to)
-            .set("salt", &instruction.salt)
-            .set("iv", &instruction.iv)
+            .set("salt", content.apply(&instruction.salt))
+            .set("iv", content.apply(&instruction.iv))
             .set("version", &instruction.version)
             .set("parent_id", &instruction.parent_id)
             .set("acct_mail", &instruction.acct_mail)
+            .set("mail_id", &instruction.mail_id)
             .set("acct_mail_account_v2", &instruction.acct_mail_account_v2)
             .set("acct_authority", &instruction.acct_authority)
-            .set("acct_system_program", &instruction.acct_system_program);
+            .set("acct_system_program", &instruction.acct_system_program)
+            .set("fee_payer", &instruction.fee_payer)
+            .set("sponsored", instruction.sponsored)
+            .set("compute_units_consumed", instruction.compute_units_consumed)
+            .set("violations", instruction.violations.join(","));
     }
 
     // Process Updatemail instructions
@@ -233,10 +396,15 @@ fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
         tables
             .create_row("updatemail_instruction", pk)
             .set("trx_hash", &instruction.trx_hash)
-            .set("body", &instruction.body)
+            .set("body", content.apply(&instruction.body))
             .set("acct_mail", &instruction.acct_mail)
+            .set("mail_id", &instruction.mail_id)
             .set("acct_authority", &instruction.acct_authority)
-            .set("acct_system_program", &instruction.acct_system_program);
+            .set("acct_system_program", &instruction.acct_system_program)
+            .set("fee_payer", &instruction.fee_payer)
+            .set("sponsored", instruction.sponsored)
+            .set("compute_units_consumed", instruction.compute_units_consumed)
+            .set("violations", instruction.violations.join(","));
     }
 
     // Process Updatemailreadstatus instructions
@@ -252,8 +420,12 @@ fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
             .create_row("updatemailreadstatus_instruction", pk)
             .set("trx_hash", &instruction.trx_hash)
             .set("acct_mail", &instruction.acct_mail)
+            .set("mail_id", &instruction.mail_id)
             .set("acct_authority", &instruction.acct_authority)
-            .set("acct_system_program", &instruction.acct_system_program);
+            .set("acct_system_program", &instruction.acct_system_program)
+            .set("fee_payer", &instruction.fee_payer)
+            .set("sponsored", instruction.sponsored)
+            .set("compute_units_consumed", instruction.compute_units_consumed);
     }
 
     // Process Updatemaillabel instructions
@@ -269,10 +441,15 @@ fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
         tables
             .create_row("updatemaillabel_instruction", pk)
             .set("trx_hash", &instruction.trx_hash)
-            .set("label", &instruction.label.to_string())
+            .set("label", instruction.label.to_string())
             .set("acct_mail", &instruction.acct_mail)
+            .set("mail_id", &instruction.mail_id)
             .set("acct_authority", &instruction.acct_authority)
-            .set("acct_system_program", &instruction.acct_system_program);
+            .set("acct_system_program", &instruction.acct_system_program)
+            .set("fee_payer", &instruction.fee_payer)
+            .set("sponsored", instruction.sponsored)
+            .set("compute_units_consumed", instruction.compute_units_consumed)
+            .set("violations", instruction.violations.join(","));
     }
 
     // Process RegisterV2 instructions
@@ -288,9 +465,14 @@ fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
             .create_row("register_v2_instruction", pk)
             .set("trx_hash", &instruction.trx_hash)
             .set("nostr_key", &instruction.nostr_key)
+            .set("nostr_key_kind", &instruction.nostr_key_kind)
+            .set("nostr_key_hex", &instruction.nostr_key_hex)
+            .set("nostr_npub", &instruction.nostr_npub)
             .set("acct_mail_account_v2", &instruction.acct_mail_account_v2)
             .set("acct_authority", &instruction.acct_authority)
-            .set("acct_system_program", &instruction.acct_system_program);
+            .set("acct_system_program", &instruction.acct_system_program)
+            .set("compute_units_consumed", instruction.compute_units_consumed)
+            .set("violations", instruction.violations.join(","));
     }
 
     // Process UpdateAccountV2 instructions
@@ -307,14 +489,19 @@ fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
             .create_row("update_account_v2_instruction", pk)
             .set("trx_hash", &instruction.trx_hash)
             .set("nostr_key", &instruction.nostr_key)
+            .set("nostr_key_kind", &instruction.nostr_key_kind)
+            .set("nostr_key_hex", &instruction.nostr_key_hex)
+            .set("nostr_npub", &instruction.nostr_npub)
             .set("mailbox", &instruction.mailbox)
             .set("acct_mail_account_v2", &instruction.acct_mail_account_v2)
-            .set("acct_authority", &instruction.acct_authority);
+            .set("acct_authority", &instruction.acct_authority)
+            .set("compute_units_consumed", instruction.compute_units_consumed)
+            .set("violations", instruction.violations.join(","));
     }
 
     // Process Sendmail instructions
     for instruction in &data.sendmail_instruction_list {
-        substreams::log::debug!("Creating DB row for Sendmail instruction: subject={}", instruction.subject);
+        substreams::log::debug!("Creating DB row for Sendmail instruction: {}", instruction.acct_mail);
         
         let pk = generate_instruction_pk(&[
             &instruction.trx_hash,
@@ -330,16 +517,21 @@ fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
         tables
             .create_row("sendmail_instruction", pk)
             .set("trx_hash", &instruction.trx_hash)
-            .set("subject", &instruction.subject)
-            .set("body", &instruction.body)
+            .set("subject", content.apply(&instruction.subject))
+            .set("body", content.apply(&instruction.body))
             .set("from_address", &instruction.from)
             .set("to_address", &instruction.to)
-            .set("salt", &instruction.salt)
-            .set("iv", &instruction.iv)
+            .set("salt", content.apply(&instruction.salt))
+            .set("iv", content.apply(&instruction.iv))
             .set("version", &instruction.version)
             .set("acct_mail", &instruction.acct_mail)
+            .set("mail_id", &instruction.mail_id)
             .set("acct_authority", &instruction.acct_authority)
-            .set("acct_system_program", &instruction.acct_system_program);
+            .set("acct_system_program", &instruction.acct_system_program)
+            .set("fee_payer", &instruction.fee_payer)
+            .set("sponsored", instruction.sponsored)
+            .set("compute_units_consumed", instruction.compute_units_consumed)
+            .set("violations", instruction.violations.join(","));
     }
 
     // Process Register instructions
@@ -355,14 +547,440 @@ fn process_instructions(data: &Data, tables: &mut DatabaseChangeTables) {
             .create_row("register_instruction", pk)
             .set("trx_hash", &instruction.trx_hash)
             .set("nostr_key", &instruction.nostr_key)
+            .set("nostr_key_kind", &instruction.nostr_key_kind)
+            .set("nostr_key_hex", &instruction.nostr_key_hex)
+            .set("nostr_npub", &instruction.nostr_npub)
             .set("acct_mail_account", &instruction.acct_mail_account)
             .set("acct_authority", &instruction.acct_authority)
-            .set("acct_system_program", &instruction.acct_system_program);
+            .set("acct_system_program", &instruction.acct_system_program)
+            .set("compute_units_consumed", instruction.compute_units_consumed)
+            .set("violations", instruction.violations.join(","));
+    }
+}
+
+/// Add one row per Solmail transaction with its signers and costs
+fn process_transaction_metas(data: &Data, tables: &mut DatabaseChangeTables) {
+    for meta in &data.transaction_meta_list {
+        substreams::log::debug!("Creating DB row for transaction: {}", meta.trx_hash);
+
+        let pk = generate_event_pk(&[&meta.trx_hash]);
+
+        tables
+            .create_row("transaction_meta", pk)
+            .set("trx_hash", &meta.trx_hash)
+            .set("slot", meta.slot)
+            .set("fee_payer", &meta.fee_payer)
+            .set("signers", meta.signers.join(","))
+            .set("num_signers", meta.signers.len() as u64)
+            .set("fee", meta.fee)
+            .set("compute_units_consumed", meta.compute_units_consumed);
+    }
+}
+
+/// Add one row per activity item, keeping the block ordering
+fn process_activity(activity: &Activity, tables: &mut DatabaseChangeTables) {
+    for item in &activity.items {
+        let kind = match item.item.as_ref() {
+            Some(kind) => kind,
+            None => continue,
+        };
+        substreams::log::debug!("Creating DB row for activity: {} #{}", item.trx_hash, item.ordinal);
+
+        let pk = generate_event_pk(&[
+            &item.trx_hash,
+            &item.ordinal.to_string(),
+        ]);
+
+        let row = tables
+            .create_row("activity", pk)
+            .set("slot", item.slot)
+            .set("block_time", item.block_time)
+            .set("trx_hash", &item.trx_hash)
+            .set("tx_index", item.tx_index)
+            .set("ordinal", item.ordinal)
+            .set("invocation_index", item.invocation_index)
+            .set("signer", &item.signer)
+            .set("program_id", &item.program_id)
+            .set("kind", kind.kind());
+
+        // First address per role, e.g. from_address / to_address / owner
+        let mut seen: Vec<&str> = Vec::new();
+        for (role, address) in kind.addresses() {
+            if seen.contains(&role) {
+                continue;
+            }
+            seen.push(role);
+            match role {
+                "from" => row.set("from_address", address),
+                "to" => row.set("to_address", address),
+                _ => row.set(role, address),
+            };
+        }
+    }
+}
+
+/// Upsert the sponsored send counters that changed in this block
+fn process_sponsor_stats(deltas: &Deltas<DeltaInt64>, tables: &mut DatabaseChangeTables) {
+    for delta in &deltas.deltas {
+        let day = key::segment_at(&delta.key, 0);
+        let sponsor = key::segment_at(&delta.key, 1);
+
+        tables
+            .upsert_row("sponsor_stats", [("day", day), ("sponsor", sponsor)])
+            .set("sponsored_sends", delta.new_value);
+    }
+}
+
+/// Upsert the daily compute unit statistics of every instruction kind seen in
+/// this block, with percentiles approximated from the stored histogram
+fn process_compute_units(clock: &Clock, activity: &Activity, store: &StoreGetInt64, tables: &mut DatabaseChangeTables) {
+    let day = day_from_clock(clock);
+    let kinds: BTreeSet<&'static str> = activity
+        .items
+        .iter()
+        .filter_map(|item| item.item.as_ref())
+        .filter(|item| item.compute_units_consumed().unwrap_or_default() > 0)
+        .map(|item| item.kind())
+        .collect();
+
+    for kind in kinds {
+        let prefix = format!("{}:{}", day, kind);
+        let count = store.get_last(format!("{}:count", prefix)).unwrap_or_default();
+        let sum = store.get_last(format!("{}:sum", prefix)).unwrap_or_default();
+        if count == 0 {
+            continue;
+        }
+        let buckets: Vec<i64> = (0..COMPUTE_UNITS_HISTOGRAM.buckets)
+            .map(|index| store.get_last(format!("{}:bucket:{}", prefix, index)).unwrap_or_default())
+            .collect();
+
+        tables
+            .upsert_row("daily_compute_units", [("day", day.as_str()), ("kind", kind)])
+            .set("invocations", count)
+            .set("avg_compute_units", sum / count)
+            .set("p50_compute_units", COMPUTE_UNITS_HISTOGRAM.percentile(&buckets, 0.5))
+            .set("p90_compute_units", COMPUTE_UNITS_HISTOGRAM.percentile(&buckets, 0.9))
+            .set("p99_compute_units", COMPUTE_UNITS_HISTOGRAM.percentile(&buckets, 0.99));
+    }
+}
+
+/// Add one row per failed Solmail transaction
+fn process_program_errors(data: &Data, tables: &mut DatabaseChangeTables) {
+    for error in &data.program_error_list {
+        substreams::log::debug!("Creating DB row for program error: {} {}", error.trx_hash, error.name);
+
+        let pk = generate_event_pk(&[&error.trx_hash]);
+
+        tables
+            .create_row("program_error", pk)
+            .set("trx_hash", &error.trx_hash)
+            .set("slot", error.slot)
+            .set("tx_index", error.tx_index)
+            .set("instruction_index", error.instruction_index)
+            .set("instruction", &error.instruction)
+            .set("code", error.code)
+            .set("name", &error.name)
+            .set("message", &error.message)
+            .set("signer", &error.signer);
+    }
+}
+
+/// Upsert the daily error counters that changed in this block
+fn process_error_stats(deltas: &Deltas<DeltaInt64>, tables: &mut DatabaseChangeTables) {
+    for delta in &deltas.deltas {
+        let day = key::segment_at(&delta.key, 0);
+        let instruction = key::segment_at(&delta.key, 1);
+        let code = key::segment_at(&delta.key, 2);
+        let name = key::segment_at(&delta.key, 3);
+
+        tables
+            .upsert_row("error_stats", [("day", day), ("instruction", instruction), ("code", code)])
+            .set("name", name)
+            .set("failures", delta.new_value);
+    }
+}
+
+/// Upsert the daily mail counters per encryption version that changed in
+/// this block
+fn process_version_stats(deltas: &Deltas<DeltaInt64>, tables: &mut DatabaseChangeTables) {
+    for delta in &deltas.deltas {
+        let day = key::segment_at(&delta.key, 0);
+        let version = key::segment_at(&delta.key, 1);
+        let column = match key::segment_at(&delta.key, 2) {
+            "mails" => "mails",
+            "unknown" => "unknown_version",
+            "mismatched" => "mismatched_lengths",
+            _ => continue,
+        };
+
+        tables
+            .upsert_row("mail_version_stats", [("day", day), ("version", version)])
+            .set(column, delta.new_value);
+    }
+}
+
+/// Upsert the daily network counters that changed in this block
+fn process_daily_stats(deltas: &Deltas<DeltaInt64>, tables: &mut DatabaseChangeTables) {
+    for delta in &deltas.deltas {
+        let day = key::segment_at(&delta.key, 0);
+        let metric = key::segment_at(&delta.key, 1);
+
+        tables
+            .upsert_row("daily_stats", day)
+            .set(metric, delta.new_value);
+    }
+}
+
+/// Upsert the per-user counters and slots that changed in this block.
+///
+/// Rows are written with the stores' new totals rather than increments, so
+/// undoing a block restores the previous values.
+fn process_user_stats(
+    counters: &Deltas<DeltaInt64>,
+    first_seen: &Deltas<DeltaInt64>,
+    last_active: &Deltas<DeltaInt64>,
+    tables: &mut DatabaseChangeTables,
+) {
+    for delta in &counters.deltas {
+        let address = key::segment_at(&delta.key, 0);
+        let metric = key::segment_at(&delta.key, 1);
+
+        tables.upsert_row("user_stats", address).set(metric, delta.new_value);
+    }
+
+    for delta in &first_seen.deltas {
+        tables
+            .upsert_row("user_stats", &delta.key)
+            .set("first_seen_slot", delta.new_value);
+    }
+
+    for delta in &last_active.deltas {
+        tables
+            .upsert_row("user_stats", &delta.key)
+            .set("last_active_slot", delta.new_value);
+    }
+}
+
+/// Upsert the sender → recipient edges that changed in this block
+fn process_interaction_edges(
+    counters: &Deltas<DeltaInt64>,
+    first_slots: &Deltas<DeltaInt64>,
+    last_slots: &Deltas<DeltaInt64>,
+    tables: &mut DatabaseChangeTables,
+) {
+    for delta in &counters.deltas {
+        let from = key::segment_at(&delta.key, 0);
+        let to = key::segment_at(&delta.key, 1);
+        let metric = key::segment_at(&delta.key, 2);
+
+        tables
+            .upsert_row("interaction_edge", [("from_address", from), ("to_address", to)])
+            .set(metric, delta.new_value);
+    }
+
+    for (deltas, column) in [(first_slots, "first_message_slot"), (last_slots, "last_message_slot")] {
+        for delta in &deltas.deltas {
+            let from = key::segment_at(&delta.key, 0);
+            let to = key::segment_at(&delta.key, 1);
+
+            tables
+                .upsert_row("interaction_edge", [("from_address", from), ("to_address", to)])
+                .set(column, delta.new_value);
+        }
+    }
+}
+
+/// Add one row per mail read for the first time in this block, and upsert
+/// the day's latency percentiles
+fn process_read_latencies(
+    clock: &Clock,
+    latencies: &MailReadLatencies,
+    store: &StoreGetInt64,
+    tables: &mut DatabaseChangeTables,
+) {
+    for latency in &latencies.mail_read_latency_list {
+        tables
+            .create_row("mail_read_latency", &latency.mail_id)
+            .set("reader", &latency.reader)
+            .set("trx_hash", &latency.trx_hash)
+            .set("sent_slot", latency.sent_slot)
+            .set("sent_at", latency.sent_at)
+            .set("read_slot", latency.read_slot)
+            .set("read_at", latency.read_at)
+            .set("latency_seconds", latency.latency_seconds);
+    }
+
+    if latencies.mail_read_latency_list.is_empty() {
+        return;
+    }
+
+    let day = day_from_clock(clock);
+    let count = store.get_last(format!("{}:count", day)).unwrap_or_default();
+    let sum = store.get_last(format!("{}:sum", day)).unwrap_or_default();
+    if count == 0 {
+        return;
+    }
+    let buckets: Vec<i64> = (0..READ_LATENCY_HISTOGRAM.buckets)
+        .map(|index| store.get_last(format!("{}:bucket:{}", day, index)).unwrap_or_default())
+        .collect();
+
+    tables
+        .upsert_row("daily_read_latency", day)
+        .set("reads", count)
+        .set("avg_latency_seconds", sum / count)
+        .set("p50_latency_seconds", READ_LATENCY_HISTOGRAM.percentile(&buckets, 0.5))
+        .set("p90_latency_seconds", READ_LATENCY_HISTOGRAM.percentile(&buckets, 0.9))
+        .set("p99_latency_seconds", READ_LATENCY_HISTOGRAM.percentile(&buckets, 0.99));
+}
+
+/// Upsert the response time percentiles of every replier in this block, both
+/// overall (empty `recipient`) and towards the mail's recipient
+fn process_response_times(reply_times: &MailReplyTimes, store: &StoreGetInt64, tables: &mut DatabaseChangeTables) {
+    let keys: BTreeSet<(&str, &str)> = reply_times
+        .mail_reply_time_list
+        .iter()
+        .flat_map(|reply| [(reply.replier.as_str(), ""), (reply.replier.as_str(), reply.recipient.as_str())])
+        .collect();
+
+    for (replier, recipient) in keys {
+        let prefix = format!("{}:{}", replier, recipient);
+        let count = store.get_last(format!("{}:count", prefix)).unwrap_or_default();
+        let sum = store.get_last(format!("{}:sum", prefix)).unwrap_or_default();
+        if count == 0 {
+            continue;
+        }
+        let buckets: Vec<i64> = (0..RESPONSE_TIME_HISTOGRAM.buckets)
+            .map(|index| store.get_last(format!("{}:bucket:{}", prefix, index)).unwrap_or_default())
+            .collect();
+
+        tables
+            .upsert_row("user_response_time", [("address", replier), ("recipient", recipient)])
+            .set("replies", count)
+            .set("avg_response_seconds", sum / count)
+            .set("p50_response_seconds", RESPONSE_TIME_HISTOGRAM.percentile(&buckets, 0.5))
+            .set("p90_response_seconds", RESPONSE_TIME_HISTOGRAM.percentile(&buckets, 0.9))
+            .set("p99_response_seconds", RESPONSE_TIME_HISTOGRAM.percentile(&buckets, 0.99));
+    }
+}
+
+/// Add one row per label change, along with the label it replaced, unknown
+/// for the first label seen on a mail.
+fn process_label_changes(label_changes: &LabelChanges, tables: &mut DatabaseChangeTables) {
+    for (index, change) in label_changes.label_change_list.iter().enumerate() {
+        let pk = generate_event_pk(&[&change.trx_hash, &change.mail_id, &index.to_string()]);
+
+        let row = tables
+            .create_row("mail_label_history", pk)
+            .set("trx_hash", &change.trx_hash)
+            .set("slot", change.slot)
+            .set("mail_id", &change.mail_id)
+            .set("owner", &change.owner)
+            .set("label", change.label);
+        if let Some(previous_label) = change.previous_label {
+            row.set("previous_label", previous_label);
+        }
+    }
+}
+
+/// Upsert the spam signals and reputation score of every sender who sent a
+/// mail in this block or whose mail was labelled
+fn process_sender_reputation(
+    data: &Data,
+    mail_senders: &StoreGetString,
+    store: &StoreGetInt64,
+    tables: &mut DatabaseChangeTables,
+) {
+    let mut senders: BTreeSet<String> = data.mail_pairs().map(|(from, _)| from.to_string()).collect();
+    senders.extend(
+        data.label_change_list
+            .iter()
+            .filter_map(|change| mail_senders.get_last(&change.mail_id)),
+    );
+
+    for sender in senders.iter().filter(|sender| !sender.is_empty()) {
+        let metric = |name: &str| store.get_last(format!("{}:{}", sender, name)).unwrap_or_default();
+        let sent = metric("sent");
+        let spam_labels = metric("spam_labels");
+
+        tables
+            .upsert_row("sender_reputation", sender)
+            .set("sent", sent)
+            .set("spam_labels", spam_labels)
+            .set("spam_reports", metric("spam_reports"))
+            .set("reporters", metric("reporters"))
+            .set("score", format!("{:.4}", sender_reputation(sent, spam_labels)));
+    }
+}
+
+/// Add one row per abuse alert
+fn process_abuse_alerts(alerts: &AbuseAlerts, tables: &mut DatabaseChangeTables) {
+    for alert in &alerts.abuse_alert_list {
+        let pk = generate_event_pk(&[&alert.sender, &alert.slot.to_string()]);
+
+        tables
+            .create_row("abuse_alert", pk)
+            .set("sender", &alert.sender)
+            .set("trx_hash", &alert.trx_hash)
+            .set("slot", alert.slot)
+            .set("window_start_slot", alert.window_start_slot)
+            .set("sends", alert.sends)
+            .set("recipients", alert.recipients)
+            .set("unregistered_recipients", alert.unregistered_recipients)
+            .set("unregistered_pct", alert.unregistered_pct)
+            .set("reasons", alert.reasons.join(","));
+    }
+}
+
+/// Upsert the V2 mailboxes whose authority or counters changed in this
+/// block, and add one `mailbox_ownership_history` row per change of
+/// authority. The owner store deltas are set at the index of the
+/// `updateAccountV2` instruction, and their old value is the previous
+/// authority.
+fn process_mailboxes(
+    data: &Data,
+    owners: &Deltas<DeltaString>,
+    counters: &Deltas<DeltaInt64>,
+    tables: &mut DatabaseChangeTables,
+) {
+    for delta in &owners.deltas {
+        if delta.operation == Operation::Delete || delta.old_value == delta.new_value {
+            continue;
+        }
+        let Some(instruction) = data.update_account_v2_instruction_list.get(delta.ordinal as usize) else {
+            continue;
+        };
+        let pk = generate_event_pk(&[&instruction.trx_hash, &delta.key, &delta.ordinal.to_string()]);
+
+        let row = tables
+            .create_row("mailbox_ownership_history", pk)
+            .set("trx_hash", &instruction.trx_hash)
+            .set("slot", data.slot)
+            .set("mailbox", &delta.key)
+            .set("authority", &delta.new_value);
+        if delta.operation != Operation::Create {
+            row.set("previous_authority", &delta.old_value);
+        }
+
+        tables
+            .upsert_row("mailbox", &delta.key)
+            .set("authority", &delta.new_value)
+            .set("authority_since_slot", data.slot);
+    }
+
+    for delta in &counters.deltas {
+        let mailbox = key::segment_at(&delta.key, 0);
+        let metric = key::segment_at(&delta.key, 1);
+        let value = match metric {
+            "unread" => delta.new_value.max(0),
+            _ => delta.new_value,
+        };
+
+        tables.upsert_row("mailbox", mailbox).set(metric, value);
     }
 }
 
 /// Generate a primary key for events by hashing the provided fields
-fn generate_event_pk(fields: &[&str]) -> String {
+pub(crate) fn generate_event_pk(fields: &[&str]) -> String {
     let mut hasher = Sha256::new();
     for field in fields {
         hasher.update(field.as_bytes());
@@ -387,6 +1005,32 @@ pub fn map_enum_mail_label(value: idl::idl::program::types::MailLabel) -> i32 {
         idl::idl::program::types::MailLabel::Read => 2,
         idl::idl::program::types::MailLabel::Trash => 3,
         idl::idl::program::types::MailLabel::Spam => 4,
-        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_db_out_params() {
+        assert_eq!(DbOutParams::parse("").unwrap(), DbOutParams::default());
+        assert!(DbOutParams::parse("content").is_err());
+        assert!(DbOutParams::parse("unknown=1").is_err());
+
+        let params = DbOutParams::parse("content=hashed&").unwrap();
+        assert_eq!(params.content, ContentPolicy::Hashed);
+        assert!(DbOutParams::parse("content=redacted").is_err());
+    }
+
+    #[test]
+    fn test_content_policy() {
+        assert_eq!(ContentPolicy::Full.apply("hello"), "hello");
+        assert_eq!(
+            ContentPolicy::Hashed.apply("hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(ContentPolicy::Length.apply("hello"), "5");
+        assert_eq!(ContentPolicy::Omitted.apply("hello"), "");
     }
 }
@@ -0,0 +1,81 @@
+use substreams::errors::Error;
+use substreams_database_change::pb::database::DatabaseChanges;
+use substreams_database_change::tables::Tables as DatabaseChangeTables;
+
+use crate::handlers::db_out::generate_event_pk;
+use crate::pb::substreams::v1::program::ProgramLogs;
+
+/// Database output of the `msg!` lines of Solmail invocations.
+///
+/// Kept apart from `db_out` so that the logs of every block are only parsed
+/// when a sink actually asks for them.
+#[substreams::handlers::map]
+pub fn db_out_program_logs(program_logs: ProgramLogs) -> Result<DatabaseChanges, Error> {
+    let mut tables: DatabaseChangeTables = DatabaseChangeTables::new();
+
+    process_program_logs(&program_logs, &mut tables);
+
+    Ok(tables.to_database_changes())
+}
+
+/// Add one row per program log line
+fn process_program_logs(program_logs: &ProgramLogs, tables: &mut DatabaseChangeTables) {
+    for log in &program_logs.program_log_list {
+        let pk = generate_event_pk(&[
+            &log.trx_hash,
+            &log.invocation_index.to_string(),
+            &log.ordinal.to_string(),
+        ]);
+
+        tables
+            .create_row("program_log", pk)
+            .set("trx_hash", &log.trx_hash)
+            .set("slot", log.slot)
+            .set("tx_index", log.tx_index)
+            .set("invocation_index", log.invocation_index)
+            .set("depth", log.depth)
+            .set("ordinal", log.ordinal)
+            .set("message", &log.message)
+            .set("failed", log.failed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::substreams::v1::program::ProgramLog;
+    use substreams_database_change::pb::database::table_change::PrimaryKey;
+
+    fn log(invocation_index: u32, ordinal: u32) -> ProgramLog {
+        ProgramLog {
+            trx_hash: "trx".to_string(),
+            invocation_index,
+            ordinal,
+            message: format!("line {}", ordinal),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_process_program_logs_keys() {
+        // Ordinals restart at 0 for every invocation of the same transaction
+        let program_logs = ProgramLogs {
+            program_log_list: vec![log(0, 0), log(0, 1), log(1, 0), log(1, 1)],
+        };
+
+        let mut tables = DatabaseChangeTables::new();
+        process_program_logs(&program_logs, &mut tables);
+
+        let changes = tables.to_database_changes().table_changes;
+        let keys: std::collections::BTreeSet<String> = changes
+            .iter()
+            .map(|change| match &change.primary_key {
+                Some(PrimaryKey::Pk(pk)) => pk.clone(),
+                other => panic!("unexpected primary key {:?}", other),
+            })
+            .collect();
+
+        assert_eq!(changes.len(), 4);
+        assert_eq!(keys.len(), 4);
+    }
+}
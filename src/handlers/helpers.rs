@@ -0,0 +1,32 @@
+use substreams::pb::substreams::Clock;
+
+/// UTC day of the block, formatted as `YYYY-MM-DD`
+pub fn day_from_clock(clock: &Clock) -> String {
+    let seconds = clock.timestamp.as_ref().map(|t| t.seconds).unwrap_or_default();
+    day_from_timestamp(seconds)
+}
+
+/// UTC day of the block, as a number of days since the unix epoch
+pub fn day_number_from_clock(clock: &Clock) -> i64 {
+    let seconds = clock.timestamp.as_ref().map(|t| t.seconds).unwrap_or_default();
+    seconds.div_euclid(86_400)
+}
+
+/// UTC day of a unix timestamp, formatted as `YYYY-MM-DD`
+pub fn day_from_timestamp(seconds: i64) -> String {
+    chrono::DateTime::from_timestamp(seconds, 0)
+        .map(|datetime| datetime.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_from_timestamp() {
+        assert_eq!(day_from_timestamp(0), "1970-01-01");
+        assert_eq!(day_from_timestamp(1_714_608_000), "2024-05-02");
+        assert_eq!(day_from_timestamp(1_714_607_999), "2024-05-01");
+    }
+}
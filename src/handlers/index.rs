@@ -0,0 +1,70 @@
+use std::collections::BTreeSet;
+
+use substreams::pb::sf::substreams::index::v1::Keys;
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Block index keys used by `blockFilter` queries to skip irrelevant blocks.
+///
+/// Emits `<role>:<pubkey>` for every address of the block (roles are `from`,
+/// `to`, `owner`, `authority` and `mailbox`), `user:<pubkey>` for the same
+/// addresses whatever their role, and `kind:<name>` for every event and
+/// instruction kind, e.g. `from:<pubkey> || to:<pubkey>`, `user:<pubkey>` or
+/// `kind:sendmail`.
+#[substreams::handlers::map]
+pub fn index(data: Data) -> Keys {
+    let keys = block_keys(data);
+
+    substreams::log::debug!("Indexed block with {} keys", keys.len());
+
+    Keys { keys }
+}
+
+/// Sorted, deduplicated index keys of a block
+fn block_keys(data: Data) -> Vec<String> {
+    let mut keys: BTreeSet<String> = BTreeSet::new();
+
+    for item in data.into_items() {
+        keys.insert(format!("kind:{}", item.kind()));
+        for (role, address) in item.addresses() {
+            keys.insert(format!("{}:{}", role, address));
+            keys.insert(format!("user:{}", address));
+        }
+    }
+
+    keys.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::substreams::v1::program::{MailV2ReadEventEvent, SendmailInstruction};
+
+    #[test]
+    fn test_block_keys() {
+        let data = Data {
+            sendmail_instruction_list: vec![SendmailInstruction {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                acct_authority: "alice".to_string(),
+                ..Default::default()
+            }],
+            mail_v2_read_event_event_list: vec![MailV2ReadEventEvent {
+                owner: "bob".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(block_keys(data), vec![
+            "authority:alice",
+            "from:alice",
+            "kind:MailV2ReadEvent",
+            "kind:sendmail",
+            "owner:bob",
+            "to:bob",
+            "user:alice",
+            "user:bob",
+        ]);
+    }
+}
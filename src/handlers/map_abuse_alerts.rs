@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use substreams::errors::Error;
+use substreams::key;
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas, StoreGet, StoreGetInt64};
+
+use crate::abuse::{window, AbuseThresholds, WindowCounts, WINDOW_SLOTS};
+use crate::pb::substreams::v1::program::{AbuseAlert, AbuseAlerts, Data};
+
+/// Senders whose mail volume over the current and previous counting window
+/// crossed one of the thresholds given as params (see [AbuseThresholds]) in
+/// this block.
+#[substreams::handlers::map]
+pub fn map_abuse_alerts(
+    params: String,
+    data: Data,
+    window_recipients: Deltas<DeltaInt64>,
+    window_stats: StoreGetInt64,
+) -> Result<AbuseAlerts, Error> {
+    let thresholds = AbuseThresholds::parse(&params)?;
+    let window = window(data.slot);
+
+    // What this block added to each sender's counts, and its last mail
+    let mut senders: BTreeMap<&str, (WindowCounts, &str)> = BTreeMap::new();
+    for (from, _, trx_hash) in data.mail_sends() {
+        let (counts, last_trx_hash) = senders.entry(from).or_default();
+        counts.sends += 1;
+        *last_trx_hash = trx_hash;
+    }
+    for delta in window_recipients.deltas.iter().filter(|delta| delta.operation == Operation::Create) {
+        if let Some((counts, _)) = senders.get_mut(key::segment_at(&delta.key, 1)) {
+            counts.recipients += 1;
+            counts.unregistered += (delta.new_value == 0) as i64;
+        }
+    }
+
+    let alerts: Vec<_> = senders
+        .into_iter()
+        .filter_map(|(sender, (added, trx_hash))| {
+            let after = span_counts(&window_stats, window, sender);
+            let reasons = thresholds.crossed(&(after - added), &after);
+            if reasons.is_empty() {
+                return None;
+            }
+
+            substreams::log::info!("Abuse alert for {}: {}", sender, reasons.join(","));
+
+            Some(AbuseAlert {
+                sender: sender.to_string(),
+                trx_hash: trx_hash.to_string(),
+                slot: data.slot,
+                window_start_slot: window.saturating_sub(1) * WINDOW_SLOTS,
+                sends: after.sends as u64,
+                recipients: after.recipients as u64,
+                unregistered_recipients: after.unregistered as u64,
+                unregistered_pct: after.unregistered_pct(),
+                reasons: reasons.iter().map(|reason| reason.to_string()).collect(),
+            })
+        })
+        .collect();
+
+    Ok(AbuseAlerts {
+        abuse_alert_list: alerts,
+    })
+}
+
+/// Counts of a sender over the current and previous window
+fn span_counts(window_stats: &StoreGetInt64, window: u64, sender: &str) -> WindowCounts {
+    let metric = |window: u64, name: &str| {
+        window_stats
+            .get_last(format!("{}:{}:{}", window, sender, name))
+            .unwrap_or_default()
+    };
+    let windows = [Some(window), window.checked_sub(1)];
+
+    windows.into_iter().flatten().fold(WindowCounts::default(), |counts, window| WindowCounts {
+        sends: counts.sends + metric(window, "sends"),
+        recipients: counts.recipients + metric(window, "recipients"),
+        unregistered: counts.unregistered + metric(window, "unregistered"),
+    })
+}
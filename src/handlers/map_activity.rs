@@ -0,0 +1,17 @@
+use substreams_solana::pb::sf::solana::r#type::v1::Block;
+
+use crate::handlers::map_program_data::block_activity;
+use crate::pb::substreams::v1::program::Activity;
+
+/// Unified activity feed: every Solmail event and instruction of the block as
+/// a single list, ordered by transaction index and ordinal.
+///
+/// This is the only module decoding the block's instructions and events;
+/// `map_program_data` and the modules downstream build on its output.
+#[substreams::handlers::map]
+pub fn map_activity(blk: Block) -> Activity {
+    let items = block_activity(&blk);
+    substreams::log::info!("Block {}: {} activity items", blk.slot, items.len());
+
+    Activity { items }
+}
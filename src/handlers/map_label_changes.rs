@@ -0,0 +1,56 @@
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas};
+
+use crate::pb::substreams::v1::program::{Data, LabelChange, LabelChanges};
+
+/// Label changes of the block along with the label each one replaced.
+///
+/// `store_mail_labels` sets each change at the ordinal of its index in
+/// `label_change_list`, so the old value of that delta is the previous label.
+/// It is unset for the first label seen on a mail.
+#[substreams::handlers::map]
+pub fn map_label_changes(data: Data, mail_labels: Deltas<DeltaInt64>) -> LabelChanges {
+    LabelChanges {
+        label_change_list: with_previous_labels(data.label_change_list, &mail_labels),
+    }
+}
+
+fn with_previous_labels(mut changes: Vec<LabelChange>, mail_labels: &Deltas<DeltaInt64>) -> Vec<LabelChange> {
+    for delta in mail_labels.deltas.iter().filter(|delta| delta.operation != Operation::Create) {
+        if let Some(change) = changes.get_mut(delta.ordinal as usize) {
+            change.previous_label = Some(delta.old_value as u64);
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_previous_labels() {
+        let change = |label: u64| LabelChange {
+            mail_id: "mail-1".to_string(),
+            label,
+            ..Default::default()
+        };
+        let delta = |operation: Operation, ordinal: u64, old_value: i64, new_value: i64| DeltaInt64 {
+            operation,
+            ordinal,
+            key: "mail-1".to_string(),
+            old_value,
+            new_value,
+        };
+
+        // First label of the mail, then relabelled within the same block
+        let mail_labels = Deltas {
+            deltas: vec![delta(Operation::Create, 0, 0, 4), delta(Operation::Update, 1, 4, 3)],
+        };
+
+        let changes = with_previous_labels(vec![change(4), change(3)], &mail_labels);
+        assert_eq!(changes[0].previous_label, None);
+        assert_eq!(changes[1].previous_label, Some(4));
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::BTreeSet;
+
+use serde_json::json;
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas, StoreGet, StoreGetString};
+
+use crate::handlers::store_nostr_accounts::account_key;
+use crate::nostr::NostrKey;
+use crate::pb::substreams::v1::program::activity_item::Item;
+use crate::pb::substreams::v1::program::{Activity, ActivityItem, NostrEvent, NostrEvents, NostrTag};
+
+/// NIP-04 encrypted direct message
+const ENCRYPTED_DIRECT_MESSAGE: u32 = 4;
+
+/// Unsigned Nostr events for the mails whose body is first seen in the block,
+/// ready to be signed and relayed by a bridge.
+///
+/// A mail is bridged once, when `store_mail_bodies` first sees its body: with
+/// the `sendmail` itself, or with the `MailV2UpdateEvent` writing the body of
+/// a `createmail`, in the same block or a later one. Later edits are not
+/// re-emitted, and mails whose body is never written are not bridged.
+///
+/// The sender and recipient are resolved to their registered nostr keys via
+/// `store_nostr_accounts`; an unregistered sender leaves `pubkey` empty.
+#[substreams::handlers::map]
+pub fn map_nostr_events(activity: Activity, accounts: StoreGetString, mail_bodies: Deltas<DeltaInt64>) -> NostrEvents {
+    let first_bodies: BTreeSet<&str> = mail_bodies
+        .deltas
+        .iter()
+        .filter(|delta| delta.operation == Operation::Create)
+        .map(|delta| delta.key.as_str())
+        .collect();
+
+    let events: Vec<_> = new_mails(&activity.items, &first_bodies)
+        .into_iter()
+        .map(|(activity, mail)| {
+            nostr_event(activity, &mail, |address| {
+                accounts.get_last(account_key(address)).map(|hex| NostrKey::parse(&hex))
+            })
+        })
+        .collect();
+
+    substreams::log::info!("{} nostr events", events.len());
+
+    NostrEvents { events }
+}
+
+/// Mails whose body is first seen in the block (`first_bodies`) along with the
+/// first item of the block about them.
+///
+/// The items of a same mail are merged, so a `createmail` gets the body of the
+/// `MailV2UpdateEvent` that follows it.
+fn new_mails<'a>(items: &'a [ActivityItem], first_bodies: &BTreeSet<&str>) -> Vec<(&'a ActivityItem, Mail<'a>)> {
+    let mut mails: Vec<(&ActivityItem, Mail)> = Vec::new();
+
+    for activity in items {
+        let Some(mail) = activity.item.as_ref().and_then(Mail::from_item) else {
+            continue;
+        };
+        if !first_bodies.contains(mail.mail_id) {
+            continue;
+        }
+
+        match mails.iter_mut().find(|(_, known)| known.mail_id == mail.mail_id) {
+            Some((_, known)) => known.merge(mail),
+            None => mails.push((activity, mail)),
+        }
+    }
+
+    mails
+}
+
+/// Fields of a new mail shared by the instructions and events that carry one
+struct Mail<'a> {
+    mail_id: &'a str,
+    from: &'a str,
+    to: &'a str,
+    parent_id: &'a str,
+    subject: &'a str,
+    body: &'a str,
+    iv: &'a str,
+    salt: &'a str,
+}
+
+impl<'a> Mail<'a> {
+    fn from_item(item: &'a Item) -> Option<Self> {
+        match item {
+            Item::SendmailInstruction(instruction) => Some(Mail {
+                mail_id: &instruction.mail_id,
+                from: &instruction.from,
+                to: &instruction.to,
+                parent_id: "",
+                subject: &instruction.subject,
+                body: &instruction.body,
+                iv: &instruction.iv,
+                salt: &instruction.salt,
+            }),
+            Item::CreatemailInstruction(instruction) => Some(Mail {
+                mail_id: &instruction.mail_id,
+                from: &instruction.from,
+                to: &instruction.to,
+                parent_id: &instruction.parent_id,
+                subject: &instruction.subject,
+                body: "",
+                iv: &instruction.iv,
+                salt: &instruction.salt,
+            }),
+            Item::MailV2UpdateEvent(event) => Some(Mail {
+                mail_id: &event.id,
+                from: &event.from,
+                to: &event.to,
+                parent_id: &event.parent_id,
+                subject: &event.subject,
+                body: &event.body,
+                iv: &event.iv,
+                salt: &event.salt,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Fill the fields of the mail with the non-empty ones of a later item of
+    /// the same mail
+    fn merge(&mut self, other: Mail<'a>) {
+        let fields = [
+            (&mut self.from, other.from),
+            (&mut self.to, other.to),
+            (&mut self.parent_id, other.parent_id),
+            (&mut self.subject, other.subject),
+            (&mut self.body, other.body),
+            (&mut self.iv, other.iv),
+            (&mut self.salt, other.salt),
+        ];
+        for (field, value) in fields {
+            if !value.is_empty() {
+                *field = value;
+            }
+        }
+    }
+}
+
+/// Build the event of a mail, resolving Solana addresses to nostr keys with
+/// `resolve`
+fn nostr_event<F>(activity: &ActivityItem, mail: &Mail, resolve: F) -> NostrEvent
+where
+    F: Fn(&str) -> Option<NostrKey>,
+{
+    let sender = resolve(mail.from);
+    let recipient = resolve(mail.to);
+
+    let mut tags: Vec<Vec<String>> = Vec::new();
+    if let Some(recipient) = &recipient {
+        tags.push(vec!["p".to_string(), recipient.hex.clone()]);
+    }
+    tags.push(address_tag("from", mail.from, sender.as_ref()));
+    tags.push(address_tag("to", mail.to, recipient.as_ref()));
+    tags.push(vec!["solmail".to_string(), mail.mail_id.to_string()]);
+    if !mail.parent_id.is_empty() {
+        tags.push(vec!["parent".to_string(), mail.parent_id.to_string()]);
+    }
+    if !mail.subject.is_empty() {
+        tags.push(vec!["subject".to_string(), mail.subject.to_string()]);
+    }
+    if !mail.salt.is_empty() {
+        tags.push(vec!["salt".to_string(), mail.salt.to_string()]);
+    }
+
+    let pubkey = sender.map(|sender| sender.hex).unwrap_or_default();
+    let content = encrypted_content(mail.body, mail.iv);
+
+    let json = json!({
+        "pubkey": pubkey,
+        "created_at": activity.block_time,
+        "kind": ENCRYPTED_DIRECT_MESSAGE,
+        "tags": tags,
+        "content": content,
+    })
+    .to_string();
+
+    NostrEvent {
+        trx_hash: activity.trx_hash.clone(),
+        slot: activity.slot,
+        ordinal: activity.ordinal,
+        source: activity.item.as_ref().map(|item| item.kind()).unwrap_or_default().to_string(),
+        mail_id: mail.mail_id.to_string(),
+        pubkey,
+        created_at: activity.block_time,
+        kind: ENCRYPTED_DIRECT_MESSAGE,
+        tags: tags.into_iter().map(|values| NostrTag { values }).collect(),
+        content,
+        json,
+    }
+}
+
+/// `[name, <solana address>, <npub>]`, without the npub when the address has
+/// no registered key
+fn address_tag(name: &str, address: &str, key: Option<&NostrKey>) -> Vec<String> {
+    let mut tag = vec![name.to_string(), address.to_string()];
+    if let Some(key) = key {
+        tag.push(key.npub.clone());
+    }
+    tag
+}
+
+/// NIP-04 content: the ciphertext followed by its IV
+fn encrypted_content(body: &str, iv: &str) -> String {
+    if body.is_empty() || iv.is_empty() {
+        return body.to_string();
+    }
+    format!("{}?iv={}", body, iv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::substreams::v1::program::{CreatemailInstruction, MailV2UpdateEventEvent, SendmailInstruction};
+
+    const HEX: &str = "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e";
+    const NPUB: &str = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+
+    #[test]
+    fn test_sendmail_event() {
+        let activity = ActivityItem {
+            trx_hash: "tx".to_string(),
+            block_time: 1_700_000_000,
+            item: Some(Item::SendmailInstruction(SendmailInstruction {
+                subject: "hi".to_string(),
+                body: "Y2lwaGVy".to_string(),
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                iv: "aXY=".to_string(),
+                mail_id: "mail".to_string(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let mail = Mail::from_item(activity.item.as_ref().unwrap()).unwrap();
+
+        // Only the recipient registered a key
+        let event = nostr_event(&activity, &mail, |address| {
+            (address == "bob").then(|| NostrKey::parse(HEX))
+        });
+
+        assert_eq!(event.kind, ENCRYPTED_DIRECT_MESSAGE);
+        assert_eq!(event.pubkey, "");
+        assert_eq!(event.source, "sendmail");
+        assert_eq!(event.content, "Y2lwaGVy?iv=aXY=");
+
+        let tags: Vec<Vec<&str>> = event
+            .tags
+            .iter()
+            .map(|tag| tag.values.iter().map(String::as_str).collect())
+            .collect();
+        assert_eq!(tags, vec![
+            vec!["p", HEX],
+            vec!["from", "alice"],
+            vec!["to", "bob", NPUB],
+            vec!["solmail", "mail"],
+            vec!["subject", "hi"],
+        ]);
+
+        let json: serde_json::Value = serde_json::from_str(&event.json).unwrap();
+        assert_eq!(json["kind"], 4);
+        assert_eq!(json["created_at"], 1_700_000_000);
+        assert_eq!(json["tags"][0][1], HEX);
+    }
+
+    fn item(ordinal: u32, item: Item) -> ActivityItem {
+        ActivityItem {
+            trx_hash: "tx".to_string(),
+            ordinal,
+            item: Some(item),
+            ..Default::default()
+        }
+    }
+
+    fn createmail(mail_id: &str) -> Item {
+        Item::CreatemailInstruction(CreatemailInstruction {
+            subject: "hi".to_string(),
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            iv: "aXY=".to_string(),
+            acct_mail: "mail-account".to_string(),
+            mail_id: mail_id.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn update_event(mail_id: &str, body: &str) -> Item {
+        Item::MailV2UpdateEvent(MailV2UpdateEventEvent {
+            id: mail_id.to_string(),
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            body: body.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_createmail_merged_with_its_event() {
+        let items = vec![
+            item(0, createmail("mail-1")),
+            item(1, update_event("mail-1", "Y2lwaGVy")),
+            // Edit of a mail whose body was written in an earlier block
+            item(2, update_event("mail-0", "ZWRpdA==")),
+        ];
+
+        let mails = new_mails(&items, &BTreeSet::from(["mail-1"]));
+        assert_eq!(mails.len(), 1);
+
+        let (activity, mail) = &mails[0];
+        let event = nostr_event(activity, mail, |_| None);
+        assert_eq!(event.source, "createmail");
+        assert_eq!(event.mail_id, "mail-1");
+        assert_eq!(event.ordinal, 0);
+        assert_eq!(event.content, "Y2lwaGVy?iv=aXY=");
+    }
+
+    #[test]
+    fn test_body_written_in_a_later_block() {
+        // The createmail alone has no body yet and is not bridged
+        let items = vec![item(0, createmail("mail-1"))];
+        assert!(new_mails(&items, &BTreeSet::new()).is_empty());
+
+        // The update writing the body, in a later block, carries the mail
+        let items = vec![item(0, update_event("mail-1", "Y2lwaGVy"))];
+        let mails = new_mails(&items, &BTreeSet::from(["mail-1"]));
+        assert_eq!(mails.len(), 1);
+
+        let (activity, mail) = &mails[0];
+        let event = nostr_event(activity, mail, |_| None);
+        assert_eq!(event.source, "MailV2UpdateEvent");
+        assert_eq!(event.content, "Y2lwaGVy");
+    }
+}
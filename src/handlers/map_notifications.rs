@@ -0,0 +1,159 @@
+use std::collections::BTreeSet;
+
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas, StoreGet, StoreGetString};
+
+use crate::pb::substreams::v1::program::{Data, Notification, Notifications};
+
+const NEW_MAIL: &str = "new_mail";
+const MAIL_READ: &str = "mail_read";
+const REPLY: &str = "reply";
+
+/// One message per occurrence a recipient should be told about, for push
+/// notification services:
+/// - `new_mail`: a mail was sent to `recipient`
+/// - `reply`: a reply was sent to `recipient`, or to a thread `recipient`
+///   started
+/// - `mail_read`: a mail of `recipient` was read for the first time by
+///   `sender`
+///
+/// Senders of replied and read mails are resolved by mail id through
+/// `store_mail_senders`; mails sent before the first indexed block are
+/// skipped.
+#[substreams::handlers::map]
+pub fn map_notifications(data: Data, mail_senders: StoreGetString, first_reads: Deltas<DeltaInt64>) -> Notifications {
+    let notifications = notifications(&data, &first_reads, |mail_id| mail_senders.get_last(mail_id));
+
+    substreams::log::info!("{} notifications", notifications.len());
+
+    Notifications { notifications }
+}
+
+/// Notifications of the block, resolving the sender of a mail id with
+/// `sender_of`
+fn notifications<F>(data: &Data, first_reads: &Deltas<DeltaInt64>, sender_of: F) -> Vec<Notification>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut notifications = Vec::new();
+    let mut notify = |kind: &str, recipient: &str, mail_id: &str, sender: &str, trx_hash: &str| {
+        if recipient.is_empty() || recipient == sender {
+            return;
+        }
+        notifications.push(Notification {
+            kind: kind.to_string(),
+            recipient: recipient.to_string(),
+            mail_id: mail_id.to_string(),
+            sender: sender.to_string(),
+            trx_hash: trx_hash.to_string(),
+            slot: data.slot,
+            block_time: data.block_time,
+        });
+    };
+
+    // The instruction and events of a same mail notify once
+    let mut sent = BTreeSet::new();
+    let mut first_sent = |mail_id: &str| mail_id.is_empty() || sent.insert(mail_id.to_string());
+
+    for instruction in &data.createmail_instruction_list {
+        if !first_sent(&instruction.mail_id) {
+            continue;
+        }
+        if instruction.parent_id.is_empty() {
+            notify(NEW_MAIL, &instruction.to, &instruction.mail_id, &instruction.from, &instruction.trx_hash);
+            continue;
+        }
+
+        notify(REPLY, &instruction.to, &instruction.mail_id, &instruction.from, &instruction.trx_hash);
+        if let Some(thread_sender) = sender_of(&instruction.parent_id) {
+            if thread_sender != instruction.to {
+                notify(REPLY, &thread_sender, &instruction.mail_id, &instruction.from, &instruction.trx_hash);
+            }
+        }
+    }
+    let mails = data
+        .sendmail_instruction_list
+        .iter()
+        .map(|instruction| (&instruction.mail_id, &instruction.from, &instruction.to, &instruction.trx_hash))
+        .chain(data.mail_v2_send_event_event_list.iter().map(|event| (&event.id, &event.from, &event.to, &event.trx_hash)))
+        .chain(data.mail_send_event_event_list.iter().map(|event| (&event.id, &event.from, &event.to, &event.trx_hash)));
+    for (mail_id, from, to, trx_hash) in mails {
+        if first_sent(mail_id) {
+            notify(NEW_MAIL, to, mail_id, from, trx_hash);
+        }
+    }
+
+    let reads = data.mail_reads();
+    for delta in first_reads.deltas.iter().filter(|delta| delta.operation == Operation::Create) {
+        let Some((mail_id, reader, trx_hash)) = reads.iter().find(|(mail_id, _, _)| *mail_id == delta.key) else {
+            continue;
+        };
+        if let Some(mail_sender) = sender_of(mail_id) {
+            notify(MAIL_READ, &mail_sender, mail_id, reader, trx_hash);
+        }
+    }
+
+    notifications
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::substreams::v1::program::{CreatemailInstruction, MailV2ReadEventEvent, MailV2SendEventEvent};
+
+    #[test]
+    fn test_reply_notifications() {
+        // alice started the thread with `mail-1` in an earlier block; bob
+        // replies to carol with `mail-2`, decoded as both the `createmail`
+        // and its `MailV2SendEvent`, and carol reads the reply
+        let data = Data {
+            createmail_instruction_list: vec![CreatemailInstruction {
+                trx_hash: "tx-1".to_string(),
+                acct_mail: "mail-account".to_string(),
+                mail_id: "mail-2".to_string(),
+                parent_id: "mail-1".to_string(),
+                from: "bob".to_string(),
+                to: "carol".to_string(),
+                ..Default::default()
+            }],
+            mail_v2_send_event_event_list: vec![MailV2SendEventEvent {
+                trx_hash: "tx-1".to_string(),
+                id: "mail-2".to_string(),
+                from: "bob".to_string(),
+                to: "carol".to_string(),
+                ..Default::default()
+            }],
+            mail_v2_read_event_event_list: vec![MailV2ReadEventEvent {
+                trx_hash: "tx-2".to_string(),
+                id: "mail-2".to_string(),
+                owner: "carol".to_string(),
+            }],
+            ..Default::default()
+        };
+        let first_reads = Deltas {
+            deltas: vec![DeltaInt64 {
+                operation: Operation::Create,
+                ordinal: 0,
+                key: "mail-2".to_string(),
+                old_value: 0,
+                new_value: 1_700_000_000,
+            }],
+        };
+
+        let notifications = notifications(&data, &first_reads, |mail_id| match mail_id {
+            "mail-1" => Some("alice".to_string()),
+            "mail-2" => Some("bob".to_string()),
+            _ => None,
+        });
+
+        let notified: Vec<_> = notifications
+            .iter()
+            .map(|n| (n.kind.as_str(), n.recipient.as_str(), n.mail_id.as_str(), n.sender.as_str()))
+            .collect();
+        assert_eq!(notified, vec![
+            (REPLY, "carol", "mail-2", "bob"),
+            (REPLY, "alice", "mail-2", "bob"),
+            (MAIL_READ, "bob", "mail-2", "carol"),
+        ]);
+    }
+}
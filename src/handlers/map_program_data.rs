@@ -2,438 +2,1095 @@ use anchor_lang::Discriminator;
 use base64::prelude::*;
 use sologger_log_context::programs_selector::ProgramsSelector;
 use sologger_log_context::sologger_log_context::LogContext;
-use substreams_solana::pb::sf::solana::r#type::v1::Block;
+use substreams_solana::base58;
+use substreams_solana::block_view::InstructionView;
+use substreams_solana::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction};
 
 use crate::idl;
+use crate::pb::substreams::v1::program::activity_item::Item;
 use crate::pb::substreams::v1::program::*;
 use crate::deserializers::*;
+use crate::nostr::NostrKey;
+use crate::validation::Validate;
 use crate::PROGRAM_ID;
 
-/// Main substreams handler for processing mail program data
+/// Main substreams handler for processing mail program data.
+///
+/// Events and instructions are decoded once by `map_activity`; they are split
+/// here into the per-kind lists, alongside what only the block carries:
+/// transaction metadata and the errors of failed transactions.
 #[substreams::handlers::map]
-pub fn map_program_data(blk: Block) -> Data {
+pub fn map_program_data(blk: Block, activity: Activity) -> Data {
     substreams::log::info!("=== PROCESSING BLOCK {} ===", blk.slot);
     substreams::log::info!("Block has {} transactions", blk.transactions.len());
 
-    // Initialize result vectors
-    let mut mail_send_event_event_list: Vec<MailSendEventEvent> = Vec::new();
-    let mut mail_v2_send_event_event_list: Vec<MailV2SendEventEvent> = Vec::new();
-    let mut mail_v2_update_event_event_list: Vec<MailV2UpdateEventEvent> = Vec::new();
-    let mut mail_v2_read_event_event_list: Vec<MailV2ReadEventEvent> = Vec::new();
-    let mut mail_v2_update_label_event_event_list: Vec<MailV2UpdateLabelEventEvent> = Vec::new();
-    let mut mail_account_v2_register_event_event_list: Vec<MailAccountV2RegisterEventEvent> = Vec::new();
-    let mut mail_account_v2_update_event_event_list: Vec<MailAccountV2UpdateEventEvent> = Vec::new();
-    let mut createmail_instruction_list: Vec<CreatemailInstruction> = Vec::new();
-    let mut updatemail_instruction_list: Vec<UpdatemailInstruction> = Vec::new();
-    let mut updatemailreadstatus_instruction_list: Vec<UpdatemailreadstatusInstruction> = Vec::new();
-    let mut updatemaillabel_instruction_list: Vec<UpdatemaillabelInstruction> = Vec::new();
-    let mut register_v2_instruction_list: Vec<RegisterV2Instruction> = Vec::new();
-    let mut update_account_v2_instruction_list: Vec<UpdateAccountV2Instruction> = Vec::new();
-    let mut sendmail_instruction_list: Vec<SendmailInstruction> = Vec::new();
-    let mut register_instruction_list: Vec<RegisterInstruction> = Vec::new();
-
-    // Process each transaction
-    blk.transactions().for_each(|transaction| {
-        let tx_id = transaction.id();
-        substreams::log::debug!("Processing transaction: {}", tx_id);
-
-        // Process events from transaction logs
-        process_transaction_events(
-            &transaction,
-            &mut mail_send_event_event_list,
-            &mut mail_v2_send_event_event_list,
-            &mut mail_v2_update_event_event_list,
-            &mut mail_v2_read_event_event_list,
-            &mut mail_v2_update_label_event_event_list,
-            &mut mail_account_v2_register_event_event_list,
-            &mut mail_account_v2_update_event_event_list,
-        );
+    let block_time = blk.block_time.as_ref().map(|t| t.timestamp).unwrap_or_default();
+    let mut data = Data {
+        slot: blk.slot,
+        block_time,
+        ..Default::default()
+    };
 
-        // Process instructions
-        process_transaction_instructions(
-            &transaction,
-            &mut createmail_instruction_list,
-            &mut updatemail_instruction_list,
-            &mut updatemailreadstatus_instruction_list,
-            &mut updatemaillabel_instruction_list,
-            &mut register_v2_instruction_list,
-            &mut update_account_v2_instruction_list,
-            &mut sendmail_instruction_list,
-            &mut register_instruction_list,
-        );
-    });
+    for items in activity.items.chunk_by(|a, b| a.tx_index == b.tx_index) {
+        if let Some(transaction) = blk.transactions.get(items[0].tx_index as usize) {
+            data.transaction_meta_list.push(transaction_meta(transaction, blk.slot));
+        }
+        data.label_change_list.extend(label_changes(items));
+    }
+
+    // Split the ordered activity back into the per-kind lists
+    activity
+        .items
+        .into_iter()
+        .filter_map(|activity| activity.item)
+        .for_each(|item| push_item(&mut data, item));
+
+    for (tx_index, transaction) in failed_transactions(&blk) {
+        if let Some(error) = program_error(transaction, blk.slot, tx_index) {
+            data.program_error_list.push(error);
+        }
+    }
 
     // Log summary
-    let total_events = mail_send_event_event_list.len() + 
-                      mail_v2_send_event_event_list.len() + 
-                      mail_v2_update_event_event_list.len() + 
-                      mail_v2_read_event_event_list.len() + 
-                      mail_v2_update_label_event_event_list.len() + 
-                      mail_account_v2_register_event_event_list.len() + 
-                      mail_account_v2_update_event_event_list.len();
-
-    let total_instructions = createmail_instruction_list.len() + 
-                           updatemail_instruction_list.len() + 
-                           updatemailreadstatus_instruction_list.len() + 
-                           updatemaillabel_instruction_list.len() + 
-                           register_v2_instruction_list.len() + 
-                           update_account_v2_instruction_list.len() + 
-                           sendmail_instruction_list.len() + 
-                           register_instruction_list.len();
+    let total_events = data.mail_send_event_event_list.len() + 
+                      data.mail_v2_send_event_event_list.len() + 
+                      data.mail_v2_update_event_event_list.len() + 
+                      data.mail_v2_read_event_event_list.len() + 
+                      data.mail_v2_update_label_event_event_list.len() + 
+                      data.mail_account_v2_register_event_event_list.len() + 
+                      data.mail_account_v2_update_event_event_list.len();
+
+    let total_instructions = data.createmail_instruction_list.len() + 
+                           data.updatemail_instruction_list.len() + 
+                           data.updatemailreadstatus_instruction_list.len() + 
+                           data.updatemaillabel_instruction_list.len() + 
+                           data.register_v2_instruction_list.len() + 
+                           data.update_account_v2_instruction_list.len() + 
+                           data.sendmail_instruction_list.len() + 
+                           data.register_instruction_list.len();
 
     substreams::log::info!("=== BLOCK {} SUMMARY ===", blk.slot);
     substreams::log::info!("Total events found: {}", total_events);
-    substreams::log::info!("- MailSendEvent: {}", mail_send_event_event_list.len());
-    substreams::log::info!("- MailV2SendEvent: {}", mail_v2_send_event_event_list.len());
-    substreams::log::info!("- MailV2UpdateEvent: {}", mail_v2_update_event_event_list.len());
-    substreams::log::info!("- MailV2ReadEvent: {}", mail_v2_read_event_event_list.len());
-    substreams::log::info!("- MailV2UpdateLabelEvent: {}", mail_v2_update_label_event_event_list.len());
-    substreams::log::info!("- MailAccountV2RegisterEvent: {}", mail_account_v2_register_event_event_list.len());
-    substreams::log::info!("- MailAccountV2UpdateEvent: {}", mail_account_v2_update_event_event_list.len());
+    substreams::log::info!("- MailSendEvent: {}", data.mail_send_event_event_list.len());
+    substreams::log::info!("- MailV2SendEvent: {}", data.mail_v2_send_event_event_list.len());
+    substreams::log::info!("- MailV2UpdateEvent: {}", data.mail_v2_update_event_event_list.len());
+    substreams::log::info!("- MailV2ReadEvent: {}", data.mail_v2_read_event_event_list.len());
+    substreams::log::info!("- MailV2UpdateLabelEvent: {}", data.mail_v2_update_label_event_event_list.len());
+    substreams::log::info!("- MailAccountV2RegisterEvent: {}", data.mail_account_v2_register_event_event_list.len());
+    substreams::log::info!("- MailAccountV2UpdateEvent: {}", data.mail_account_v2_update_event_event_list.len());
     substreams::log::info!("Total instructions found: {}", total_instructions);
+    substreams::log::info!("Program errors found: {}", data.program_error_list.len());
+
+    data
+}
+
+/// Decode every Solmail instruction and event of the block's successful
+/// transactions, in execution order
+pub(crate) fn block_activity(blk: &Block) -> Vec<ActivityItem> {
+    let block_time = blk.block_time.as_ref().map(|t| t.timestamp).unwrap_or_default();
+
+    successful_transactions(blk)
+        .flat_map(|(tx_index, transaction)| {
+            transaction_activity(transaction, blk.slot, block_time, tx_index)
+        })
+        .collect()
+}
 
-    Data {
-        mail_send_event_event_list,
-        mail_v2_send_event_event_list,
-        mail_v2_update_event_event_list,
-        mail_v2_read_event_event_list,
-        mail_v2_update_label_event_event_list,
-        mail_account_v2_register_event_event_list,
-        mail_account_v2_update_event_event_list,
-        createmail_instruction_list,
-        updatemail_instruction_list,
-        updatemailreadstatus_instruction_list,
-        updatemaillabel_instruction_list,
-        register_v2_instruction_list,
-        update_account_v2_instruction_list,
-        sendmail_instruction_list,
-        register_instruction_list,
+/// Merge every `updatemaillabel` instruction of a transaction, which carries
+/// the new label, with the `MailV2UpdateLabelEvent` emitted by the same
+/// invocation (same `invocation_index`), which carries the owner. The
+/// instruction's authority stands in for the owner when no event was emitted;
+/// instructions without a mail id are skipped.
+///
+/// `previous_label` is left unset here, `map_label_changes` fills it from
+/// `store_mail_labels`.
+fn label_changes(items: &[ActivityItem]) -> Vec<LabelChange> {
+    items
+        .iter()
+        .filter_map(|activity| match &activity.item {
+            Some(Item::UpdatemaillabelInstruction(instruction)) if !instruction.mail_id.is_empty() => {
+                Some((activity, instruction))
+            }
+            _ => None,
+        })
+        .map(|(activity, instruction)| {
+            let owner = items
+                .iter()
+                .filter(|event| event.invocation_index == activity.invocation_index)
+                .find_map(|event| match &event.item {
+                    Some(Item::MailV2UpdateLabelEvent(event)) => Some(&event.owner),
+                    _ => None,
+                })
+                .unwrap_or(&instruction.acct_authority);
+
+            LabelChange {
+                trx_hash: activity.trx_hash.clone(),
+                mail_id: instruction.mail_id.clone(),
+                owner: owner.clone(),
+                label: instruction.label,
+                slot: activity.slot,
+                previous_label: None,
+            }
+        })
+        .collect()
+}
+
+/// Successful transactions of the block along with their index in the block
+fn successful_transactions(blk: &Block) -> impl Iterator<Item = (u32, &ConfirmedTransaction)> {
+    blk.transactions
+        .iter()
+        .enumerate()
+        .filter(|(_, transaction)| transaction.meta.as_ref().is_some_and(|meta| meta.err.is_none()))
+        .map(|(tx_index, transaction)| (tx_index as u32, transaction))
+}
+
+/// Failed transactions of the block along with their index in the block
+fn failed_transactions(blk: &Block) -> impl Iterator<Item = (u32, &ConfirmedTransaction)> {
+    blk.transactions
+        .iter()
+        .enumerate()
+        .filter(|(_, transaction)| transaction.meta.as_ref().is_some_and(|meta| meta.err.is_some()))
+        .map(|(tx_index, transaction)| (tx_index as u32, transaction))
+}
+
+/// Decode why Solmail rejected a failed transaction, from `meta.err` and the
+/// `Error Code:` log Anchor writes. Transactions that failed outside of
+/// Solmail yield `None`.
+fn program_error(transaction: &ConfirmedTransaction, slot: u64, tx_index: u32) -> Option<ProgramError> {
+    let err = transaction.meta.as_ref()?.err.as_ref()?;
+    let instruction_error = deserialize_instruction_error(&err.err);
+    let instruction_index = instruction_error.as_ref().map(|error| error.instruction_index as u32);
+    let log_contexts = program_log_contexts(transaction);
+    let anchor_error = log_contexts
+        .iter()
+        .flat_map(|context| context.log_messages.iter())
+        .find_map(|log| parse_anchor_error_log(log));
+
+    let failing_instruction = instruction_index
+        .and_then(|index| transaction.compiled_instructions().nth(index as usize))
+        .filter(|inst| inst.program_id().to_string() == PROGRAM_ID);
+    if anchor_error.is_none() && failing_instruction.is_none() {
+        return None;
     }
+
+    // Name the failing Solmail instruction, or the first one when Solmail was invoked through CPI
+    let instruction = failing_instruction
+        .as_ref()
+        .and_then(|inst| instruction_kind(inst.data()))
+        .or_else(|| {
+            transaction
+                .walk_instructions()
+                .filter(|inst| inst.program_id().to_string() == PROGRAM_ID)
+                .find_map(|inst| instruction_kind(inst.data()))
+        })
+        .unwrap_or_default();
+
+    let (code, name, message) = match (anchor_error, instruction_error.and_then(|error| error.custom_code)) {
+        (Some(anchor_error), _) => (anchor_error.code, anchor_error.name, anchor_error.message),
+        (None, Some(code)) => {
+            let (name, message) = idl::errors::program_error(code).unwrap_or_default();
+            (code, name.to_string(), message.to_string())
+        }
+        // Not a custom error, keep the runtime's reason, e.g. a missing signature
+        (None, None) => {
+            let reason = log_contexts.iter().flat_map(|context| context.errors.iter()).last();
+            (0, String::new(), reason.cloned().unwrap_or_default())
+        }
+    };
+
+    substreams::log::info!("❌ Solmail error in {}: {} {} ({})", transaction.id(), code, name, instruction);
+
+    Some(ProgramError {
+        trx_hash: transaction.id(),
+        slot,
+        tx_index,
+        instruction_index: instruction_index.unwrap_or_default(),
+        instruction: instruction.to_string(),
+        code,
+        name,
+        message,
+        signer: fee_payer(transaction),
+    })
 }
 
-/// Process events from transaction logs
-#[allow(clippy::too_many_arguments)]
-fn process_transaction_events(
-    transaction: &substreams_solana::pb::sf::solana::r#type::v1::ConfirmedTransaction,
-    mail_send_event_event_list: &mut Vec<MailSendEventEvent>,
-    mail_v2_send_event_event_list: &mut Vec<MailV2SendEventEvent>,
-    mail_v2_update_event_event_list: &mut Vec<MailV2UpdateEventEvent>,
-    mail_v2_read_event_event_list: &mut Vec<MailV2ReadEventEvent>,
-    mail_v2_update_label_event_event_list: &mut Vec<MailV2UpdateLabelEventEvent>,
-    mail_account_v2_register_event_event_list: &mut Vec<MailAccountV2RegisterEventEvent>,
-    mail_account_v2_update_event_event_list: &mut Vec<MailAccountV2UpdateEventEvent>,
-) {
-    let meta_wrapped = &transaction.meta;
-    if meta_wrapped.is_none() {
-        return;
+/// Decode the Solmail activity of a single transaction.
+///
+/// Each Solmail invocation is emitted as its instruction followed by the events
+/// it logged, mail instructions taking their `mail_id` from those events.
+///
+/// Invocations found by walking the instructions and the program's log
+/// contexts both follow execution order, so they are paired in turn as long as
+/// the context sits at the invocation's depth under the same root program. An
+/// invocation whose logs were truncated away gets no context.
+fn transaction_activity(
+    transaction: &ConfirmedTransaction,
+    slot: u64,
+    block_time: i64,
+    tx_index: u32,
+) -> Vec<ActivityItem> {
+    let tx_id = transaction.id();
+    substreams::log::debug!("Processing transaction: {}", tx_id);
+
+    let log_contexts = program_log_contexts(transaction);
+    let mut contexts = log_contexts.iter().peekable();
+
+    let signer = fee_payer(transaction);
+    // Items along with the position of the invocation they come from
+    let mut items: Vec<(u32, Item)> = Vec::new();
+    let mut invocation_index = 0;
+    for inst in transaction
+        .walk_instructions()
+        .filter(|inst| inst.program_id().to_string() == PROGRAM_ID)
+    {
+        let context = contexts.next_if(|context| is_invocation_context(context, &inst));
+        let events: Vec<Item> = context
+            .map(|context| context.data_logs.iter().filter_map(|data| decode_event(data, &tx_id)).collect())
+            .unwrap_or_default();
+
+        if let Some(mut item) = decode_instruction(&inst, &tx_id) {
+            if let (Some(compute_units), Some(context)) = (item.compute_units_consumed_mut(), context) {
+                *compute_units = consumed_compute_units(context).unwrap_or_default();
+            }
+            // The mail account is not the mail id, the events of the invocation carry it
+            if let Some(mail_id) = item.mail_id_mut() {
+                *mail_id = events.iter().find_map(Item::mail_id).unwrap_or_default().to_string();
+            }
+            items.push((invocation_index, item));
+        }
+        items.extend(events.into_iter().map(|event| (invocation_index, event)));
+        invocation_index += 1;
     }
-    let meta = meta_wrapped.as_ref().unwrap();
 
-    if meta.err.is_some() {
-        return;
+    // Log contexts without a matching instruction still carry valid events
+    for context in contexts {
+        let events = context.data_logs.iter().filter_map(|data| decode_event(data, &tx_id));
+        items.extend(events.map(|event| (invocation_index, event)));
+        invocation_index += 1;
     }
 
-    let programs_selector: ProgramsSelector = ProgramsSelector::new(&["*".to_string()]);
-    let log_contexts = LogContext::parse_logs_basic(&meta.log_messages, &programs_selector);
+    items
+        .into_iter()
+        .map(|(invocation_index, item)| (invocation_index, classify_payer(item, &signer)))
+        .enumerate()
+        .map(|(ordinal, (invocation_index, item))| ActivityItem {
+            slot,
+            block_time,
+            trx_hash: tx_id.clone(),
+            tx_index,
+            ordinal: ordinal as u32,
+            signer: signer.clone(),
+            program_id: PROGRAM_ID.to_string(),
+            invocation_index,
+            item: Some(item),
+        })
+        .collect()
+}
 
-    log_contexts
-        .iter()
-        .filter(|context| context.program_id == PROGRAM_ID)
-        .for_each(|context| {
-            context.data_logs.iter().for_each(|data| {
-                if let Ok(decoded) = BASE64_STANDARD.decode(data) {
-                    if decoded.len() < 8 {
-                        return;
-                    }
-
-                    let slice_discriminator: [u8; 8] = decoded[0..8].try_into().expect("error");
-                    let static_discriminator_slice: &'static [u8] = Box::leak(Box::new(slice_discriminator));
-
-                    match static_discriminator_slice {
-                        idl::idl::program::events::MailSendEvent::DISCRIMINATOR => {
-                            if let Ok(event) = deserialize_mail_send_event(&decoded[8..]) {
-                                substreams::log::info!("✅ Successfully processed MailSendEvent: from={}, to={}, id={}", 
-                                    event.from, event.to, event.id);
-                                
-                                mail_send_event_event_list.push(MailSendEventEvent {
-                                    trx_hash: transaction.id(),
-                                    from: event.from.to_string(),
-                                    to: event.to.to_string(),
-                                    id: event.id,
-                                });
-                            } else {
-                                substreams::log::debug!("❌ Failed to deserialize MailSendEvent");
-                            }
-                        }
-                        idl::idl::program::events::MailV2SendEvent::DISCRIMINATOR => {
-                            if let Ok(event) = deserialize_mail_v2_send_event(&decoded[8..]) {
-                                substreams::log::info!("✅ Successfully processed MailV2SendEvent: from={}, to={}, id={}, mailbox={}", 
-                                    event.from, event.to, event.id, event.mailbox);
-                                
-                                mail_v2_send_event_event_list.push(MailV2SendEventEvent {
-                                    trx_hash: transaction.id(),
-                                    from: event.from.to_string(),
-                                    to: event.to.to_string(),
-                                    id: event.id,
-                                    mailbox: event.mailbox.to_string(),
-                                });
-                            } else {
-                                substreams::log::debug!("❌ Failed to deserialize MailV2SendEvent");
-                            }
-                        }
-                        idl::idl::program::events::MailV2UpdateEvent::DISCRIMINATOR => {
-                            if let Ok(event) = deserialize_mail_v2_update_event(&decoded[8..]) {
-                                substreams::log::info!("✅ Successfully processed MailV2UpdateEvent: from={}, to={}, id={}, subject={}", 
-                                    event.from, event.to, event.id, event.subject);
-                                
-                                mail_v2_update_event_event_list.push(MailV2UpdateEventEvent {
-                                    trx_hash: transaction.id(),
-                                    from: event.from.to_string(),
-                                    to: event.to.to_string(),
-                                    id: event.id,
-                                    mailbox: event.mailbox.to_string(),
-                                    parent_id: event.parent_id,
-                                    mark_as_read: event.mark_as_read,
-                                    created_at: event.created_at,
-                                    subject: event.subject,
-                                    body: event.body,
-                                    authority: event.authority.to_string(),
-                                    iv: event.iv,
-                                    salt: event.salt,
-                                    version: event.version,
-                                });
-                            } else {
-                                substreams::log::debug!("❌ Failed to deserialize MailV2UpdateEvent");
-                            }
-                        }
-                        idl::idl::program::events::MailV2ReadEvent::DISCRIMINATOR => {
-                            if let Ok(event) = deserialize_mail_v2_read_event(&decoded[8..]) {
-                                substreams::log::info!("✅ Successfully processed MailV2ReadEvent: id={}, owner={}", 
-                                    event.id, event.owner);
-                                
-                                mail_v2_read_event_event_list.push(MailV2ReadEventEvent {
-                                    trx_hash: transaction.id(),
-                                    id: event.id,
-                                    owner: event.owner.to_string(),
-                                });
-                            } else {
-                                substreams::log::debug!("❌ Failed to deserialize MailV2ReadEvent");
-                            }
-                        }
-                        idl::idl::program::events::MailV2UpdateLabelEvent::DISCRIMINATOR => {
-                            if let Ok(event) = deserialize_mail_v2_update_label_event(&decoded[8..]) {
-                                substreams::log::info!("✅ Successfully processed MailV2UpdateLabelEvent: id={}, owner={}", 
-                                    event.id, event.owner);
-                                
-                                mail_v2_update_label_event_event_list.push(MailV2UpdateLabelEventEvent {
-                                    trx_hash: transaction.id(),
-                                    id: event.id,
-                                    owner: event.owner.to_string(),
-                                });
-                            } else {
-                                substreams::log::debug!("❌ Failed to deserialize MailV2UpdateLabelEvent");
-                            }
-                        }
-                        idl::idl::program::events::MailAccountV2RegisterEvent::DISCRIMINATOR => {
-                            if let Ok(event) = deserialize_mail_account_v2_register_event(&decoded[8..]) {
-                                substreams::log::info!("✅ Successfully processed MailAccountV2RegisterEvent: owner={}, account={}", 
-                                    event.owner, event.account);
-                                
-                                mail_account_v2_register_event_event_list.push(MailAccountV2RegisterEventEvent {
-                                    trx_hash: transaction.id(),
-                                    owner: event.owner.to_string(),
-                                    account: event.account.to_string(),
-                                });
-                            } else {
-                                substreams::log::debug!("❌ Failed to deserialize MailAccountV2RegisterEvent");
-                            }
-                        }
-                        idl::idl::program::events::MailAccountV2UpdateEvent::DISCRIMINATOR => {
-                            if let Ok(event) = deserialize_mail_account_v2_update_event(&decoded[8..]) {
-                                substreams::log::info!("✅ Successfully processed MailAccountV2UpdateEvent: owner={}, account={}", 
-                                    event.owner, event.account);
-                                
-                                mail_account_v2_update_event_event_list.push(MailAccountV2UpdateEventEvent {
-                                    trx_hash: transaction.id(),
-                                    owner: event.owner.to_string(),
-                                    account: event.account.to_string(),
-                                });
-                            } else {
-                                substreams::log::debug!("❌ Failed to deserialize MailAccountV2UpdateEvent");
-                            }
-                        }
-                        _ => {
-                            substreams::log::debug!("Unknown event discriminator: {:?}", slice_discriminator);
-                        }
-                    }
-                }
-            });
-        });
+/// Whether a log context was written by the given Solmail invocation: it must
+/// sit at the invocation's call depth and, when nested, under the same root
+/// program
+fn is_invocation_context(context: &LogContext, inst: &InstructionView) -> bool {
+    let same_depth = match (inst.is_root(), inst.stack_height()) {
+        (true, _) => context.depth == 1,
+        // Older blocks do not record stack heights, only tell inner from root
+        (false, 0) => context.depth > 1,
+        (false, stack_height) => context.depth == stack_height as usize,
+    };
+    let same_root = context.depth == 1
+        || context.parent_program_id == inst.compiled_instruction().program_id().to_string();
+
+    same_depth && same_root
 }
 
-/// Process instructions from transaction
-#[allow(clippy::too_many_arguments)]
-fn process_transaction_instructions(
-    transaction: &substreams_solana::pb::sf::solana::r#type::v1::ConfirmedTransaction,
-    createmail_instruction_list: &mut Vec<CreatemailInstruction>,
-    updatemail_instruction_list: &mut Vec<UpdatemailInstruction>,
-    updatemailreadstatus_instruction_list: &mut Vec<UpdatemailreadstatusInstruction>,
-    updatemaillabel_instruction_list: &mut Vec<UpdatemaillabelInstruction>,
-    register_v2_instruction_list: &mut Vec<RegisterV2Instruction>,
-    update_account_v2_instruction_list: &mut Vec<UpdateAccountV2Instruction>,
-    sendmail_instruction_list: &mut Vec<SendmailInstruction>,
-    register_instruction_list: &mut Vec<RegisterInstruction>,
-) {
-    let instructions: Vec<_> = transaction
-        .walk_instructions()
+/// Parse the transaction logs into the log contexts of Solmail invocations
+pub(crate) fn program_log_contexts(transaction: &ConfirmedTransaction) -> Vec<LogContext> {
+    let meta = match transaction.meta.as_ref() {
+        Some(meta) => meta,
+        None => return Vec::new(),
+    };
+
+    let programs_selector: ProgramsSelector = ProgramsSelector::new(&["*".to_string()]);
+    LogContext::parse_logs_basic(&meta.log_messages, &programs_selector)
         .into_iter()
-        .filter(|inst| inst.program_id().to_string() == PROGRAM_ID)
-        .collect();
-
-    instructions.iter().for_each(|inst| {
-        let slice_u8: &[u8] = &inst.data()[..];
-
-        if slice_u8.len() >= 8 {
-            if &slice_u8[0..8] == idl::idl::program::client::args::Createmail::DISCRIMINATOR {
-                if let Ok(instruction) = deserialize_createmail_instruction(&slice_u8[8..]) {
-                    substreams::log::info!("✅ Successfully processed Createmail instruction: subject={}", instruction.subject);
-                    
-                    let accts = inst.accounts();
-                    createmail_instruction_list.push(CreatemailInstruction {
-                        trx_hash: transaction.id(),
-                        subject: instruction.subject,
-                        from: instruction.from.to_string(),
-                        to: instruction.to.to_string(),
-                        salt: instruction.salt,
-                        iv: instruction.iv,
-                        version: instruction.version,
-                        parent_id: instruction.parent_id,
-                        acct_mail: accts.get(0).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_mail_account_v2: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_authority: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_system_program: accts.get(3).map(|a| a.to_string()).unwrap_or_default(),
-                    });
-                } else {
-                    substreams::log::debug!("❌ Failed to deserialize Createmail instruction");
-                }
+        .filter(|context| context.program_id == PROGRAM_ID)
+        .collect()
+}
+
+/// Compute units reported by the `Program <id> consumed X of Y compute units`
+/// line closing a Solmail invocation
+fn consumed_compute_units(context: &LogContext) -> Option<u64> {
+    let prefix = format!("Program {} consumed ", PROGRAM_ID);
+
+    context.raw_logs.iter().rev().find_map(|log| {
+        log.strip_prefix(&prefix)?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    })
+}
+
+/// The fee payer is always the first account of the message
+fn fee_payer(transaction: &ConfirmedTransaction) -> String {
+    transaction
+        .transaction
+        .as_ref()
+        .and_then(|trx| trx.message.as_ref())
+        .and_then(|message| message.account_keys.first())
+        .map(base58::encode)
+        .unwrap_or_default()
+}
+
+/// Record the fee payer on mail instructions and flag them as sponsored when
+/// someone other than the `authority` account paid for the transaction.
+///
+/// Registration instructions and events are left alone: their payer is on the
+/// `TransactionMeta` of the same transaction.
+fn classify_payer(mut item: Item, fee_payer: &str) -> Item {
+    let (payer, sponsored, authority) = match &mut item {
+        Item::CreatemailInstruction(instruction) => (&mut instruction.fee_payer, &mut instruction.sponsored, &instruction.acct_authority),
+        Item::UpdatemailInstruction(instruction) => (&mut instruction.fee_payer, &mut instruction.sponsored, &instruction.acct_authority),
+        Item::UpdatemailreadstatusInstruction(instruction) => (&mut instruction.fee_payer, &mut instruction.sponsored, &instruction.acct_authority),
+        Item::UpdatemaillabelInstruction(instruction) => (&mut instruction.fee_payer, &mut instruction.sponsored, &instruction.acct_authority),
+        Item::SendmailInstruction(instruction) => (&mut instruction.fee_payer, &mut instruction.sponsored, &instruction.acct_authority),
+        _ => return item,
+    };
+
+    *payer = fee_payer.to_string();
+    *sponsored = !fee_payer.is_empty() && !authority.is_empty() && fee_payer != authority;
+    item
+}
+
+/// Who signed and paid for the transaction, and what it cost
+fn transaction_meta(transaction: &ConfirmedTransaction, slot: u64) -> TransactionMeta {
+    let signers: Vec<String> = transaction
+        .transaction
+        .as_ref()
+        .and_then(|trx| trx.message.as_ref())
+        .map(|message| {
+            let num_signers = message
+                .header
+                .as_ref()
+                .map(|header| header.num_required_signatures as usize)
+                .unwrap_or(1);
+            message.account_keys.iter().take(num_signers).map(base58::encode).collect()
+        })
+        .unwrap_or_default();
+    let meta = transaction.meta.as_ref();
+
+    TransactionMeta {
+        trx_hash: transaction.id(),
+        slot,
+        fee_payer: fee_payer(transaction),
+        signers,
+        fee: meta.map(|meta| meta.fee).unwrap_or_default(),
+        compute_units_consumed: meta.and_then(|meta| meta.compute_units_consumed).unwrap_or_default(),
+    }
+}
+
+/// Append a decoded item to the matching list of the block output
+fn push_item(data: &mut Data, item: Item) {
+    match item {
+        Item::MailSendEvent(event) => data.mail_send_event_event_list.push(event),
+        Item::MailV2SendEvent(event) => data.mail_v2_send_event_event_list.push(event),
+        Item::MailV2UpdateEvent(event) => data.mail_v2_update_event_event_list.push(event),
+        Item::MailV2ReadEvent(event) => data.mail_v2_read_event_event_list.push(event),
+        Item::MailV2UpdateLabelEvent(event) => data.mail_v2_update_label_event_event_list.push(event),
+        Item::MailAccountV2RegisterEvent(event) => data.mail_account_v2_register_event_event_list.push(event),
+        Item::MailAccountV2UpdateEvent(event) => data.mail_account_v2_update_event_event_list.push(event),
+        Item::CreatemailInstruction(instruction) => data.createmail_instruction_list.push(instruction),
+        Item::UpdatemailInstruction(instruction) => data.updatemail_instruction_list.push(instruction),
+        Item::UpdatemailreadstatusInstruction(instruction) => data.updatemailreadstatus_instruction_list.push(instruction),
+        Item::UpdatemaillabelInstruction(instruction) => data.updatemaillabel_instruction_list.push(instruction),
+        Item::RegisterV2Instruction(instruction) => data.register_v2_instruction_list.push(instruction),
+        Item::UpdateAccountV2Instruction(instruction) => data.update_account_v2_instruction_list.push(instruction),
+        Item::SendmailInstruction(instruction) => data.sendmail_instruction_list.push(instruction),
+        Item::RegisterInstruction(instruction) => data.register_instruction_list.push(instruction),
+    }
+}
+
+/// Decode a base64 `Program data:` log into a Solmail event
+fn decode_event(data: &str, trx_hash: &str) -> Option<Item> {
+    let decoded = BASE64_STANDARD.decode(data).ok()?;
+    if decoded.len() < 8 {
+        return None;
+    }
+
+    let discriminator = &decoded[0..8];
+
+    match discriminator {
+        idl::idl::program::events::MailSendEvent::DISCRIMINATOR => {
+            if let Ok(event) = deserialize_mail_send_event(&decoded[8..]) {
+                substreams::log::info!("✅ Successfully processed MailSendEvent: from={}, to={}, id={}", 
+                    event.from, event.to, event.id);
+                
+                Some(Item::MailSendEvent(MailSendEventEvent {
+                    trx_hash: trx_hash.to_string(),
+                    from: event.from.to_string(),
+                    to: event.to.to_string(),
+                    id: event.id,
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize MailSendEvent");
+                None
+            }
+        }
+        idl::idl::program::events::MailV2SendEvent::DISCRIMINATOR => {
+            if let Ok(event) = deserialize_mail_v2_send_event(&decoded[8..]) {
+                substreams::log::info!("✅ Successfully processed MailV2SendEvent: from={}, to={}, id={}, mailbox={}", 
+                    event.from, event.to, event.id, event.mailbox);
+                
+                Some(Item::MailV2SendEvent(MailV2SendEventEvent {
+                    trx_hash: trx_hash.to_string(),
+                    from: event.from.to_string(),
+                    to: event.to.to_string(),
+                    id: event.id,
+                    mailbox: event.mailbox.to_string(),
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize MailV2SendEvent");
+                None
+            }
+        }
+        idl::idl::program::events::MailV2UpdateEvent::DISCRIMINATOR => {
+            if let Ok(event) = deserialize_mail_v2_update_event(&decoded[8..]) {
+                substreams::log::info!("✅ Successfully processed MailV2UpdateEvent: from={}, to={}, id={}, subject={}", 
+                    event.from, event.to, event.id, event.subject);
+                
+                Some(Item::MailV2UpdateEvent(MailV2UpdateEventEvent {
+                    trx_hash: trx_hash.to_string(),
+                    from: event.from.to_string(),
+                    to: event.to.to_string(),
+                    id: event.id,
+                    mailbox: event.mailbox.to_string(),
+                    parent_id: event.parent_id,
+                    mark_as_read: event.mark_as_read,
+                    created_at: event.created_at,
+                    subject: event.subject,
+                    body: event.body,
+                    authority: event.authority.to_string(),
+                    iv: event.iv,
+                    salt: event.salt,
+                    version: event.version,
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize MailV2UpdateEvent");
+                None
+            }
+        }
+        idl::idl::program::events::MailV2ReadEvent::DISCRIMINATOR => {
+            if let Ok(event) = deserialize_mail_v2_read_event(&decoded[8..]) {
+                substreams::log::info!("✅ Successfully processed MailV2ReadEvent: id={}, owner={}", 
+                    event.id, event.owner);
+                
+                Some(Item::MailV2ReadEvent(MailV2ReadEventEvent {
+                    trx_hash: trx_hash.to_string(),
+                    id: event.id,
+                    owner: event.owner.to_string(),
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize MailV2ReadEvent");
+                None
+            }
+        }
+        idl::idl::program::events::MailV2UpdateLabelEvent::DISCRIMINATOR => {
+            if let Ok(event) = deserialize_mail_v2_update_label_event(&decoded[8..]) {
+                substreams::log::info!("✅ Successfully processed MailV2UpdateLabelEvent: id={}, owner={}", 
+                    event.id, event.owner);
+                
+                Some(Item::MailV2UpdateLabelEvent(MailV2UpdateLabelEventEvent {
+                    trx_hash: trx_hash.to_string(),
+                    id: event.id,
+                    owner: event.owner.to_string(),
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize MailV2UpdateLabelEvent");
+                None
+            }
+        }
+        idl::idl::program::events::MailAccountV2RegisterEvent::DISCRIMINATOR => {
+            if let Ok(event) = deserialize_mail_account_v2_register_event(&decoded[8..]) {
+                substreams::log::info!("✅ Successfully processed MailAccountV2RegisterEvent: owner={}, account={}", 
+                    event.owner, event.account);
+                
+                Some(Item::MailAccountV2RegisterEvent(MailAccountV2RegisterEventEvent {
+                    trx_hash: trx_hash.to_string(),
+                    owner: event.owner.to_string(),
+                    account: event.account.to_string(),
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize MailAccountV2RegisterEvent");
+                None
+            }
+        }
+        idl::idl::program::events::MailAccountV2UpdateEvent::DISCRIMINATOR => {
+            if let Ok(event) = deserialize_mail_account_v2_update_event(&decoded[8..]) {
+                substreams::log::info!("✅ Successfully processed MailAccountV2UpdateEvent: owner={}, account={}", 
+                    event.owner, event.account);
+                
+                Some(Item::MailAccountV2UpdateEvent(MailAccountV2UpdateEventEvent {
+                    trx_hash: trx_hash.to_string(),
+                    owner: event.owner.to_string(),
+                    account: event.account.to_string(),
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize MailAccountV2UpdateEvent");
+                None
             }
-            else if &slice_u8[0..8] == idl::idl::program::client::args::Updatemail::DISCRIMINATOR {
-                if let Ok(instruction) = deserialize_updatemail_instruction(&slice_u8[8..]) {
-                    substreams::log::info!("✅ Successfully processed Updatemail instruction");
-                    
-                    let accts = inst.accounts();
-                    updatemail_instruction_list.push(UpdatemailInstruction {
-                        trx_hash: transaction.id(),
-                        body: instruction.body,
-                        acct_mail: accts.get(0).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_authority: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
-                    });
-                } else {
-                    substreams::log::debug!("❌ Failed to deserialize Updatemail instruction");
-                }
+        }
+        _ => {
+            substreams::log::debug!("Unknown event discriminator: {:?}", discriminator);
+            None
+        }
+    }
+}
+
+/// IDL name of a Solmail instruction, from its discriminator
+fn instruction_kind(data: &[u8]) -> Option<&'static str> {
+    let discriminator = data.get(0..8)?;
+
+    match discriminator {
+        idl::idl::program::client::args::Createmail::DISCRIMINATOR => Some("createmail"),
+        idl::idl::program::client::args::Updatemail::DISCRIMINATOR => Some("updatemail"),
+        idl::idl::program::client::args::Updatemailreadstatus::DISCRIMINATOR => Some("updatemailreadstatus"),
+        idl::idl::program::client::args::Updatemaillabel::DISCRIMINATOR => Some("updatemaillabel"),
+        idl::idl::program::client::args::RegisterV2::DISCRIMINATOR => Some("registerV2"),
+        idl::idl::program::client::args::UpdateAccountV2::DISCRIMINATOR => Some("updateAccountV2"),
+        idl::idl::program::client::args::Sendmail::DISCRIMINATOR => Some("sendmail"),
+        idl::idl::program::client::args::Register::DISCRIMINATOR => Some("register"),
+        _ => None,
+    }
+}
+
+/// Decode a Solmail instruction
+fn decode_instruction(inst: &InstructionView, trx_hash: &str) -> Option<Item> {
+    let slice_u8: &[u8] = &inst.data()[..];
+
+    if slice_u8.len() < 8 {
+        return None;
+    }
+
+    match &slice_u8[0..8] {
+        idl::idl::program::client::args::Createmail::DISCRIMINATOR => {
+            if let Ok(instruction) = deserialize_createmail_instruction(&slice_u8[8..]) {
+                substreams::log::info!("✅ Successfully processed Createmail instruction: subject={}", instruction.subject);
+                
+                let accts = inst.accounts();
+                let authority = accts.get(2).map(|a| a.to_string()).unwrap_or_default();
+                let violations = instruction.violations(&authority);
+                Some(Item::CreatemailInstruction(CreatemailInstruction {
+                    trx_hash: trx_hash.to_string(),
+                    subject: instruction.subject,
+                    from: instruction.from.to_string(),
+                    to: instruction.to.to_string(),
+                    salt: instruction.salt,
+                    iv: instruction.iv,
+                    version: instruction.version,
+                    parent_id: instruction.parent_id,
+                    acct_mail: accts.first().map(|a| a.to_string()).unwrap_or_default(),
+                    acct_mail_account_v2: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
+                    acct_authority: authority,
+                    acct_system_program: accts.get(3).map(|a| a.to_string()).unwrap_or_default(),
+                    violations: violations.iter().map(|v| v.to_string()).collect(),
+                    ..Default::default()
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize Createmail instruction");
+                None
+            }
+        }
+        idl::idl::program::client::args::Updatemail::DISCRIMINATOR => {
+            if let Ok(instruction) = deserialize_updatemail_instruction(&slice_u8[8..]) {
+                substreams::log::info!("✅ Successfully processed Updatemail instruction");
+                
+                let accts = inst.accounts();
+                let authority = accts.get(1).map(|a| a.to_string()).unwrap_or_default();
+                let violations = instruction.violations(&authority);
+                Some(Item::UpdatemailInstruction(UpdatemailInstruction {
+                    trx_hash: trx_hash.to_string(),
+                    body: instruction.body,
+                    acct_mail: accts.first().map(|a| a.to_string()).unwrap_or_default(),
+                    acct_authority: authority,
+                    acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
+                    violations: violations.iter().map(|v| v.to_string()).collect(),
+                    ..Default::default()
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize Updatemail instruction");
+                None
             }
-            else if &slice_u8[0..8] == idl::idl::program::client::args::Updatemailreadstatus::DISCRIMINATOR {
-                if let Ok(_instruction) = deserialize_updatemailreadstatus_instruction(&slice_u8[8..]) {
-                    substreams::log::info!("✅ Successfully processed Updatemailreadstatus instruction");
-                    
-                    let accts = inst.accounts();
-                    updatemailreadstatus_instruction_list.push(UpdatemailreadstatusInstruction {
-                        trx_hash: transaction.id(),
-                        acct_mail: accts.get(0).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_authority: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
-                    });
-                } else {
-                    substreams::log::debug!("❌ Failed to deserialize Updatemailreadstatus instruction");
-                }
+        }
+        idl::idl::program::client::args::Updatemailreadstatus::DISCRIMINATOR => {
+            if let Ok(_instruction) = deserialize_updatemailreadstatus_instruction(&slice_u8[8..]) {
+                substreams::log::info!("✅ Successfully processed Updatemailreadstatus instruction");
+                
+                let accts = inst.accounts();
+                Some(Item::UpdatemailreadstatusInstruction(UpdatemailreadstatusInstruction {
+                    trx_hash: trx_hash.to_string(),
+                    acct_mail: accts.first().map(|a| a.to_string()).unwrap_or_default(),
+                    acct_authority: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
+                    acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
+                    ..Default::default()
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize Updatemailreadstatus instruction");
+                None
             }
-            else if &slice_u8[0..8] == idl::idl::program::client::args::Updatemaillabel::DISCRIMINATOR {
-                if let Ok(instruction) = deserialize_updatemaillabel_instruction(&slice_u8[8..]) {
-                    substreams::log::info!("✅ Successfully processed Updatemaillabel instruction");
-                    
-                    let accts = inst.accounts();
-                    updatemaillabel_instruction_list.push(UpdatemaillabelInstruction {
-                        trx_hash: transaction.id(),
-                        label: instruction.label as u64,
-                        acct_mail: accts.get(0).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_authority: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
-                    });
-                } else {
-                    substreams::log::debug!("❌ Failed to deserialize Updatemaillabel instruction");
-                }
+        }
+        idl::idl::program::client::args::Updatemaillabel::DISCRIMINATOR => {
+            if let Ok(instruction) = deserialize_updatemaillabel_instruction(&slice_u8[8..]) {
+                substreams::log::info!("✅ Successfully processed Updatemaillabel instruction");
+                
+                let accts = inst.accounts();
+                let authority = accts.get(1).map(|a| a.to_string()).unwrap_or_default();
+                let violations = instruction.violations(&authority);
+                Some(Item::UpdatemaillabelInstruction(UpdatemaillabelInstruction {
+                    trx_hash: trx_hash.to_string(),
+                    label: instruction.label as u64,
+                    acct_mail: accts.first().map(|a| a.to_string()).unwrap_or_default(),
+                    acct_authority: authority,
+                    acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
+                    violations: violations.iter().map(|v| v.to_string()).collect(),
+                    ..Default::default()
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize Updatemaillabel instruction");
+                None
             }
-            else if &slice_u8[0..8] == idl::idl::program::client::args::RegisterV2::DISCRIMINATOR {
-                if let Ok(instruction) = deserialize_register_v2_instruction(&slice_u8[8..]) {
-                    substreams::log::info!("✅ Successfully processed RegisterV2 instruction");
-                    
-                    let accts = inst.accounts();
-                    register_v2_instruction_list.push(RegisterV2Instruction {
-                        trx_hash: transaction.id(),
-                        nostr_key: instruction.nostr_key,
-                        acct_mail_account_v2: accts.get(0).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_authority: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
-                    });
-                } else {
-                    substreams::log::debug!("❌ Failed to deserialize RegisterV2 instruction");
-                }
+        }
+        idl::idl::program::client::args::RegisterV2::DISCRIMINATOR => {
+            if let Ok(instruction) = deserialize_register_v2_instruction(&slice_u8[8..]) {
+                substreams::log::info!("✅ Successfully processed RegisterV2 instruction");
+                
+                let accts = inst.accounts();
+                let authority = accts.get(1).map(|a| a.to_string()).unwrap_or_default();
+                let violations = instruction.violations(&authority);
+                let nostr_key = NostrKey::parse(&instruction.nostr_key);
+                Some(Item::RegisterV2Instruction(RegisterV2Instruction {
+                    trx_hash: trx_hash.to_string(),
+                    nostr_key: instruction.nostr_key,
+                    acct_mail_account_v2: accts.first().map(|a| a.to_string()).unwrap_or_default(),
+                    acct_authority: authority,
+                    acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
+                    violations: violations.iter().map(|v| v.to_string()).collect(),
+                    nostr_key_kind: nostr_key.kind.as_str().to_string(),
+                    nostr_key_hex: nostr_key.hex,
+                    nostr_npub: nostr_key.npub,
+                    ..Default::default()
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize RegisterV2 instruction");
+                None
             }
-            else if &slice_u8[0..8] == idl::idl::program::client::args::UpdateAccountV2::DISCRIMINATOR {
-                if let Ok(instruction) = deserialize_update_account_v2_instruction(&slice_u8[8..]) {
-                    substreams::log::info!("✅ Successfully processed UpdateAccountV2 instruction");
-                    
-                    let accts = inst.accounts();
-                    update_account_v2_instruction_list.push(UpdateAccountV2Instruction {
-                        trx_hash: transaction.id(),
-                        nostr_key: instruction.nostr_key,
-                        mailbox: instruction.mailbox.to_string(),
-                        acct_mail_account_v2: accts.get(0).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_authority: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
-                    });
-                } else {
-                    substreams::log::debug!("❌ Failed to deserialize UpdateAccountV2 instruction");
-                }
+        }
+        idl::idl::program::client::args::UpdateAccountV2::DISCRIMINATOR => {
+            if let Ok(instruction) = deserialize_update_account_v2_instruction(&slice_u8[8..]) {
+                substreams::log::info!("✅ Successfully processed UpdateAccountV2 instruction");
+                
+                let accts = inst.accounts();
+                let authority = accts.get(1).map(|a| a.to_string()).unwrap_or_default();
+                let violations = instruction.violations(&authority);
+                let nostr_key = NostrKey::parse(&instruction.nostr_key);
+                Some(Item::UpdateAccountV2Instruction(UpdateAccountV2Instruction {
+                    trx_hash: trx_hash.to_string(),
+                    nostr_key: instruction.nostr_key,
+                    mailbox: instruction.mailbox.to_string(),
+                    acct_mail_account_v2: accts.first().map(|a| a.to_string()).unwrap_or_default(),
+                    acct_authority: authority,
+                    violations: violations.iter().map(|v| v.to_string()).collect(),
+                    nostr_key_kind: nostr_key.kind.as_str().to_string(),
+                    nostr_key_hex: nostr_key.hex,
+                    nostr_npub: nostr_key.npub,
+                    ..Default::default()
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize UpdateAccountV2 instruction");
+                None
+            }
+        }
+        idl::idl::program::client::args::Sendmail::DISCRIMINATOR => {
+            if let Ok(instruction) = deserialize_sendmail_instruction(&slice_u8[8..]) {
+                substreams::log::info!("✅ Successfully processed Sendmail instruction: subject={}", instruction.subject);
+                
+                let accts = inst.accounts();
+                let authority = accts.get(1).map(|a| a.to_string()).unwrap_or_default();
+                let violations = instruction.violations(&authority);
+                Some(Item::SendmailInstruction(SendmailInstruction {
+                    trx_hash: trx_hash.to_string(),
+                    subject: instruction.subject,
+                    body: instruction.body,
+                    from: instruction.from.to_string(),
+                    to: instruction.to.to_string(),
+                    salt: instruction.salt,
+                    iv: instruction.iv,
+                    version: instruction.version,
+                    acct_mail: accts.first().map(|a| a.to_string()).unwrap_or_default(),
+                    acct_authority: authority,
+                    acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
+                    violations: violations.iter().map(|v| v.to_string()).collect(),
+                    ..Default::default()
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize Sendmail instruction");
+                None
+            }
+        }
+        idl::idl::program::client::args::Register::DISCRIMINATOR => {
+            if let Ok(instruction) = deserialize_register_instruction(&slice_u8[8..]) {
+                substreams::log::info!("✅ Successfully processed Register instruction");
+                
+                let accts = inst.accounts();
+                let authority = accts.get(1).map(|a| a.to_string()).unwrap_or_default();
+                let violations = instruction.violations(&authority);
+                let nostr_key = NostrKey::parse(&instruction.nostr_key);
+                Some(Item::RegisterInstruction(RegisterInstruction {
+                    trx_hash: trx_hash.to_string(),
+                    nostr_key: instruction.nostr_key,
+                    acct_mail_account: accts.first().map(|a| a.to_string()).unwrap_or_default(),
+                    acct_authority: authority,
+                    acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
+                    violations: violations.iter().map(|v| v.to_string()).collect(),
+                    nostr_key_kind: nostr_key.kind.as_str().to_string(),
+                    nostr_key_hex: nostr_key.hex,
+                    nostr_npub: nostr_key.npub,
+                    ..Default::default()
+                }))
+            } else {
+                substreams::log::debug!("❌ Failed to deserialize Register instruction");
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use substreams_solana::pb::sf::solana::r#type::v1::{
+        CompiledInstruction, InnerInstruction, InnerInstructions, Message, MessageHeader, Transaction,
+        TransactionError, TransactionStatusMeta,
+    };
+
+    const PAYER: u8 = 1;
+    const AUTHORITY: u8 = 2;
+    const MAIL: u8 = 3;
+    const ROUTER: u8 = 4;
+    const SYSTEM: u8 = 5;
+    const SOLMAIL: u8 = 6;
+
+    fn account_keys() -> Vec<Vec<u8>> {
+        let mut keys: Vec<Vec<u8>> = (1..=5u8).map(|key| vec![key; 32]).collect();
+        keys.push(base58::decode(PROGRAM_ID).unwrap());
+        keys
+    }
+
+    fn address(index: u8) -> String {
+        base58::encode(&account_keys()[index as usize - 1])
+    }
+
+    fn router() -> String {
+        address(ROUTER)
+    }
+
+    /// Accounts of a mail instruction, as indexes into [account_keys]
+    fn mail_accounts() -> Vec<u8> {
+        vec![MAIL - 1, AUTHORITY - 1, SYSTEM - 1]
+    }
+
+    fn read_status_data() -> Vec<u8> {
+        idl::idl::program::client::args::Updatemailreadstatus::DISCRIMINATOR.to_vec()
+    }
+
+    fn label_data(label: u8) -> Vec<u8> {
+        let mut data = idl::idl::program::client::args::Updatemaillabel::DISCRIMINATOR.to_vec();
+        data.push(label);
+        data
+    }
+
+    /// `Program data:` log of a `MailV2ReadEvent`
+    fn read_event_log(id: &str) -> String {
+        let mut data = idl::idl::program::events::MailV2ReadEvent::DISCRIMINATOR.to_vec();
+        data.extend((id.len() as u32).to_le_bytes());
+        data.extend(id.as_bytes());
+        data.extend([AUTHORITY; 32]);
+        format!("Program data: {}", BASE64_STANDARD.encode(data))
+    }
+
+    fn inner(program: u8, data: Vec<u8>, stack_height: u32) -> InnerInstruction {
+        InnerInstruction {
+            program_id_index: (program - 1) as u32,
+            accounts: mail_accounts(),
+            data,
+            stack_height: Some(stack_height),
+        }
+    }
+
+    fn transaction(
+        instructions: Vec<(u8, Vec<u8>)>,
+        inner_instructions: Vec<InnerInstructions>,
+        log_messages: Vec<String>,
+    ) -> ConfirmedTransaction {
+        ConfirmedTransaction {
+            transaction: Some(Transaction {
+                signatures: vec![vec![9; 64]],
+                message: Some(Message {
+                    header: Some(MessageHeader { num_required_signatures: 1, ..Default::default() }),
+                    account_keys: account_keys(),
+                    instructions: instructions
+                        .into_iter()
+                        .map(|(program, data)| CompiledInstruction {
+                            program_id_index: (program - 1) as u32,
+                            accounts: mail_accounts(),
+                            data,
+                        })
+                        .collect(),
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(TransactionStatusMeta { inner_instructions, log_messages, ..Default::default() }),
+        }
+    }
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.replace("SOLMAIL", PROGRAM_ID).replace("ROUTER", &router()))
+            .collect()
+    }
+
+    fn compute_units(items: &[ActivityItem]) -> Vec<u64> {
+        items
+            .iter()
+            .filter_map(|activity| activity.item.clone()?.compute_units_consumed_mut().copied())
+            .collect()
+    }
+
+    #[test]
+    fn test_transaction_activity_nested_invocations() {
+        // A router CPIs twice into Solmail, then Solmail is invoked directly
+        let trx = transaction(
+            vec![(ROUTER, vec![0]), (SOLMAIL, read_status_data())],
+            vec![InnerInstructions {
+                index: 0,
+                instructions: vec![inner(SOLMAIL, read_status_data(), 2), inner(SOLMAIL, label_data(4), 2)],
+            }],
+            logs(&[
+                "Program ROUTER invoke [1]",
+                "Program SOLMAIL invoke [2]",
+                "Program log: Instruction: Updatemailreadstatus",
+                &read_event_log("mail-7"),
+                "Program SOLMAIL consumed 1000 of 190000 compute units",
+                "Program SOLMAIL success",
+                "Program SOLMAIL invoke [2]",
+                "Program log: Instruction: Updatemaillabel",
+                "Program SOLMAIL consumed 2000 of 180000 compute units",
+                "Program SOLMAIL success",
+                "Program ROUTER consumed 5000 of 200000 compute units",
+                "Program ROUTER success",
+                "Program SOLMAIL invoke [1]",
+                "Program log: Instruction: Updatemailreadstatus",
+                "Program SOLMAIL consumed 3000 of 195000 compute units",
+                "Program SOLMAIL success",
+            ]),
+        );
+
+        let items = transaction_activity(&trx, 42, 1_700_000_000, 0);
+        assert_eq!(items.len(), 4);
+        assert_eq!(compute_units(&items), vec![1000, 2000, 3000]);
+        let invocations: Vec<u32> = items.iter().map(|item| item.invocation_index).collect();
+        assert_eq!(invocations, vec![0, 0, 1, 2]);
+        // The read instruction takes the id of the event it emitted
+        match (&items[0].item, &items[1].item) {
+            (Some(Item::UpdatemailreadstatusInstruction(instruction)), Some(Item::MailV2ReadEvent(event))) => {
+                assert_eq!(instruction.mail_id, "mail-7");
+                assert_eq!(event.id, "mail-7");
             }
-            else if &slice_u8[0..8] == idl::idl::program::client::args::Sendmail::DISCRIMINATOR {
-                if let Ok(instruction) = deserialize_sendmail_instruction(&slice_u8[8..]) {
-                    substreams::log::info!("✅ Successfully processed Sendmail instruction: subject={}", instruction.subject);
-                    
-                    let accts = inst.accounts();
-                    sendmail_instruction_list.push(SendmailInstruction {
-                        trx_hash: transaction.id(),
-                        subject: instruction.subject,
-                        body: instruction.body,
-                        from: instruction.from.to_string(),
-                        to: instruction.to.to_string(),
-                        salt: instruction.salt,
-                        iv: instruction.iv,
-                        version: instruction.version,
-                        acct_mail: accts.get(0).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_authority: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
-                    });
-                } else {
-                    substreams::log::debug!("❌ Failed to deserialize Sendmail instruction");
-                }
+            items => panic!("unexpected items {:?}", items),
+        }
+        match &items[2].item {
+            Some(Item::UpdatemaillabelInstruction(instruction)) => {
+                assert_eq!(instruction.label, 4);
+                assert_eq!(instruction.mail_id, "");
             }
-            else if &slice_u8[0..8] == idl::idl::program::client::args::Register::DISCRIMINATOR {
-                if let Ok(instruction) = deserialize_register_instruction(&slice_u8[8..]) {
-                    substreams::log::info!("✅ Successfully processed Register instruction");
-                    
-                    let accts = inst.accounts();
-                    register_instruction_list.push(RegisterInstruction {
-                        trx_hash: transaction.id(),
-                        nostr_key: instruction.nostr_key,
-                        acct_mail_account: accts.get(0).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_authority: accts.get(1).map(|a| a.to_string()).unwrap_or_default(),
-                        acct_system_program: accts.get(2).map(|a| a.to_string()).unwrap_or_default(),
-                    });
-                } else {
-                    substreams::log::debug!("❌ Failed to deserialize Register instruction");
-                }
+            item => panic!("unexpected item {:?}", item),
+        }
+        assert_eq!(items[3].ordinal, 3);
+        assert_eq!(items[3].signer, address(PAYER));
+    }
+
+    #[test]
+    fn test_transaction_activity_truncated_logs() {
+        // Logs stop inside the first nested invocation, the second one gets no context
+        let trx = transaction(
+            vec![(ROUTER, vec![0])],
+            vec![InnerInstructions {
+                index: 0,
+                instructions: vec![inner(SOLMAIL, read_status_data(), 2), inner(SOLMAIL, label_data(3), 3)],
+            }],
+            logs(&[
+                "Program ROUTER invoke [1]",
+                "Program SOLMAIL invoke [2]",
+                "Program SOLMAIL consumed 1000 of 190000 compute units",
+                "Program SOLMAIL success",
+                "Log truncated",
+            ]),
+        );
+
+        let items = transaction_activity(&trx, 42, 1_700_000_000, 0);
+        assert_eq!(compute_units(&items), vec![1000, 0]);
+    }
+
+    #[test]
+    fn test_is_invocation_context() {
+        let trx = transaction(
+            vec![(ROUTER, vec![0])],
+            vec![InnerInstructions { index: 0, instructions: vec![inner(SOLMAIL, read_status_data(), 2)] }],
+            logs(&["Program ROUTER invoke [1]", "Program SOLMAIL invoke [2]", "Program SOLMAIL success"]),
+        );
+        let inst = trx.walk_instructions().find(|inst| inst.program_id().to_string() == PROGRAM_ID).unwrap();
+        let context = |lines: &[&str]| program_log_contexts(&transaction(vec![], vec![], logs(lines))).remove(0);
+
+        assert!(is_invocation_context(&context(&["Program ROUTER invoke [1]", "Program SOLMAIL invoke [2]"]), &inst));
+        // Deeper than the instruction
+        let deeper = context(&["Program ROUTER invoke [1]", "Program ROUTER invoke [2]", "Program SOLMAIL invoke [3]"]);
+        assert!(!is_invocation_context(&deeper, &inst));
+        // Under another root program
+        let other_root = format!("Program {} invoke [1]", address(SYSTEM));
+        assert!(!is_invocation_context(&context(&[&other_root, "Program SOLMAIL invoke [2]"]), &inst));
+        // A top-level invocation
+        assert!(!is_invocation_context(&context(&["Program SOLMAIL invoke [1]"]), &inst));
+    }
+
+    #[test]
+    fn test_consumed_compute_units() {
+        let trx = transaction(
+            vec![(SOLMAIL, read_status_data())],
+            vec![],
+            logs(&[
+                "Program SOLMAIL invoke [1]",
+                "Program log: consumed 7 of 8 compute units",
+                "Program SOLMAIL consumed 4242 of 200000 compute units",
+                "Program SOLMAIL success",
+            ]),
+        );
+        let contexts = program_log_contexts(&trx);
+        assert_eq!(consumed_compute_units(&contexts[0]), Some(4242));
+
+        let trx = transaction(vec![(SOLMAIL, read_status_data())], vec![], logs(&["Program SOLMAIL invoke [1]"]));
+        assert_eq!(consumed_compute_units(&program_log_contexts(&trx)[0]), None);
+    }
+
+    #[test]
+    fn test_classify_payer() {
+        let instruction = |authority: &str| {
+            Item::UpdatemailreadstatusInstruction(UpdatemailreadstatusInstruction {
+                acct_authority: authority.to_string(),
+                ..Default::default()
+            })
+        };
+
+        match classify_payer(instruction("alice"), "relayer") {
+            Item::UpdatemailreadstatusInstruction(instruction) => {
+                assert_eq!(instruction.fee_payer, "relayer");
+                assert!(instruction.sponsored);
             }
+            _ => unreachable!(),
+        }
+        match classify_payer(instruction("alice"), "alice") {
+            Item::UpdatemailreadstatusInstruction(instruction) => assert!(!instruction.sponsored),
+            _ => unreachable!(),
+        }
+        match classify_payer(instruction(""), "relayer") {
+            Item::UpdatemailreadstatusInstruction(instruction) => assert!(!instruction.sponsored),
+            _ => unreachable!(),
         }
-    });
+
+        // Events carry no payer
+        let event = Item::MailV2ReadEvent(MailV2ReadEventEvent::default());
+        assert_eq!(classify_payer(event.clone(), "relayer"), event);
+    }
+
+    #[test]
+    fn test_program_error() {
+        let mut trx = transaction(
+            vec![(ROUTER, vec![0]), (SOLMAIL, label_data(9))],
+            vec![],
+            logs(&[
+                "Program ROUTER invoke [1]",
+                "Program ROUTER success",
+                "Program SOLMAIL invoke [1]",
+                "Program log: Instruction: Updatemaillabel",
+                "Program log: AnchorError occurred. Error Code: InvalidInstruction. Error Number: 6000. Error Message: Invalid instruction.",
+                "Program SOLMAIL failed: custom program error: 0x1770",
+            ]),
+        );
+        // InstructionError(1, Custom(6000))
+        trx.meta.as_mut().unwrap().err = Some(TransactionError { err: vec![8, 0, 0, 0, 1, 25, 0, 0, 0, 0x70, 0x17, 0, 0] });
+
+        let error = program_error(&trx, 42, 3).unwrap();
+        assert_eq!(error.code, 6000);
+        assert_eq!(error.name, "InvalidInstruction");
+        assert_eq!(error.message, "Invalid instruction");
+        assert_eq!(error.instruction, "updatemaillabel");
+        assert_eq!(error.instruction_index, 1);
+        assert_eq!(error.tx_index, 3);
+        assert_eq!(error.signer, address(PAYER));
+
+        // Failing outside of Solmail: InstructionError(0, Custom(1))
+        let mut trx = transaction(
+            vec![(ROUTER, vec![0])],
+            vec![],
+            logs(&["Program ROUTER invoke [1]", "Program ROUTER failed: custom program error: 0x1"]),
+        );
+        trx.meta.as_mut().unwrap().err = Some(TransactionError { err: vec![8, 0, 0, 0, 0, 25, 0, 0, 0, 1, 0, 0, 0] });
+        assert_eq!(program_error(&trx, 42, 0), None);
+    }
+
+    #[test]
+    fn test_label_changes() {
+        let item = |invocation_index: u32, item: Item| ActivityItem {
+            slot: 42,
+            trx_hash: "trx1".to_string(),
+            invocation_index,
+            item: Some(item),
+            ..Default::default()
+        };
+        let instruction = |invocation_index: u32, label: u64, mail_id: &str, authority: &str| {
+            item(invocation_index, Item::UpdatemaillabelInstruction(UpdatemaillabelInstruction {
+                label,
+                acct_mail: "mail-account".to_string(),
+                acct_authority: authority.to_string(),
+                mail_id: mail_id.to_string(),
+                ..Default::default()
+            }))
+        };
+        let event = |invocation_index: u32, owner: &str| {
+            item(invocation_index, Item::MailV2UpdateLabelEvent(MailV2UpdateLabelEventEvent {
+                trx_hash: "trx1".to_string(),
+                id: "mail1".to_string(),
+                owner: owner.to_string(),
+            }))
+        };
+
+        // The second invocation emitted no event, so neither the event of the
+        // first one nor the one of an unpaired log context is borrowed; the
+        // last invocation has no mail id
+        let items = vec![
+            instruction(0, 4, "mail1", "authority1"),
+            event(0, "owner1"),
+            instruction(1, 3, "mail1", "authority2"),
+            instruction(2, 2, "", "authority3"),
+            event(3, "owner3"),
+        ];
+
+        let changes = label_changes(&items);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].mail_id, "mail1");
+        assert_eq!(changes[0].owner, "owner1");
+        assert_eq!(changes[0].label, 4);
+        assert_eq!(changes[0].slot, 42);
+        assert_eq!(changes[1].owner, "authority2");
+        assert_eq!(changes[1].label, 3);
+    }
 }
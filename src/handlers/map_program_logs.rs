@@ -0,0 +1,39 @@
+use substreams_solana::pb::sf::solana::r#type::v1::Block;
+
+use crate::handlers::map_program_data::program_log_contexts;
+use crate::pb::substreams::v1::program::{ProgramLog, ProgramLogs};
+
+/// Plain `Program log:` lines (`msg!` output) of every Solmail invocation,
+/// failed transactions included, so unusual transactions can be debugged
+/// without fetching them again from an RPC node
+#[substreams::handlers::map]
+pub fn map_program_logs(blk: Block) -> ProgramLogs {
+    let mut program_log_list: Vec<ProgramLog> = Vec::new();
+
+    for (tx_index, transaction) in blk.transactions.iter().enumerate() {
+        let failed = match transaction.meta.as_ref() {
+            Some(meta) => meta.err.is_some(),
+            None => continue,
+        };
+        let trx_hash = transaction.id();
+
+        for (invocation_index, context) in program_log_contexts(transaction).iter().enumerate() {
+            for (ordinal, message) in context.log_messages.iter().enumerate() {
+                program_log_list.push(ProgramLog {
+                    trx_hash: trx_hash.clone(),
+                    slot: blk.slot,
+                    tx_index: tx_index as u32,
+                    invocation_index: invocation_index as u32,
+                    depth: context.depth as u32,
+                    ordinal: ordinal as u32,
+                    message: message.clone(),
+                    failed,
+                });
+            }
+        }
+    }
+
+    substreams::log::info!("Block {}: {} program log lines", blk.slot, program_log_list.len());
+
+    ProgramLogs { program_log_list }
+}
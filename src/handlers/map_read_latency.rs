@@ -0,0 +1,42 @@
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas, StoreGet, StoreGetInt64};
+
+use crate::pb::substreams::v1::program::{Data, MailReadLatencies, MailReadLatency};
+
+/// Time from send to first read of the mails first read in this block.
+///
+/// Mails sent before the first indexed block have no send time and are
+/// skipped.
+#[substreams::handlers::map]
+pub fn map_read_latency(data: Data, sent_at: StoreGetInt64, first_reads: Deltas<DeltaInt64>) -> MailReadLatencies {
+    let reads = data.mail_reads();
+
+    let latencies: Vec<_> = first_reads
+        .deltas
+        .iter()
+        .filter(|delta| delta.operation == Operation::Create)
+        .filter_map(|delta| {
+            let mail = delta.key.as_str();
+            let sent_time = sent_at.get_last(format!("{}:time", mail))?;
+            let sent_slot = sent_at.get_last(format!("{}:slot", mail)).unwrap_or_default();
+            let (_, reader, trx_hash) = reads.iter().find(|(read, _, _)| *read == mail)?;
+
+            Some(MailReadLatency {
+                mail_id: mail.to_string(),
+                reader: reader.to_string(),
+                trx_hash: trx_hash.to_string(),
+                sent_slot: sent_slot as u64,
+                sent_at: sent_time,
+                read_slot: data.slot,
+                read_at: delta.new_value,
+                latency_seconds: (delta.new_value - sent_time).max(0) as u64,
+            })
+        })
+        .collect();
+
+    substreams::log::info!("{} first reads with a known send time", latencies.len());
+
+    MailReadLatencies {
+        mail_read_latency_list: latencies,
+    }
+}
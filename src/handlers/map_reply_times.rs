@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use substreams::store::{StoreGet, StoreGetInt64};
+
+use crate::pb::substreams::v1::program::{Data, MailReplyTime, MailReplyTimes};
+
+/// Time between each reply sent in this block and its parent mail.
+///
+/// Replies are `createmail` instructions and `MailV2UpdateEvent` events with
+/// a `parent_id`, deduplicated by mail id. Only replies first sent in this block
+/// count, so later updates of a reply are not measured again. Replies to
+/// mails sent before the first indexed block are skipped.
+#[substreams::handlers::map]
+pub fn map_reply_times(data: Data, sent_at: StoreGetInt64) -> MailReplyTimes {
+    let reply_times: Vec<_> = replies(&data)
+        .into_iter()
+        .filter(|(reply, (parent, _, _, _))| !reply.is_empty() && !parent.is_empty())
+        .filter(|(reply, _)| sent_at.get_last(format!("{}:slot", reply)) == Some(data.slot as i64))
+        .filter_map(|(reply, (parent, replier, recipient, trx_hash))| {
+            let parent_sent_at = sent_at.get_last(format!("{}:time", parent))?;
+
+            Some(MailReplyTime {
+                reply_id: reply.to_string(),
+                parent_id: parent.to_string(),
+                replier: replier.to_string(),
+                recipient: recipient.to_string(),
+                trx_hash: trx_hash.to_string(),
+                slot: data.slot,
+                parent_sent_at,
+                replied_at: data.block_time,
+                response_seconds: (data.block_time - parent_sent_at).max(0) as u64,
+            })
+        })
+        .collect();
+
+    substreams::log::info!("{} replies with a known parent send time", reply_times.len());
+
+    MailReplyTimes {
+        mail_reply_time_list: reply_times,
+    }
+}
+
+/// Parent, replier, recipient and transaction of each reply of the block,
+/// keyed by mail id, so a `createmail` and the `MailV2UpdateEvent` of its
+/// invocation are one reply
+fn replies(data: &Data) -> BTreeMap<&str, (&str, &str, &str, &str)> {
+    let mut replies: BTreeMap<&str, (&str, &str, &str, &str)> = BTreeMap::new();
+    for instruction in &data.createmail_instruction_list {
+        replies.entry(&instruction.mail_id).or_insert((
+            &instruction.parent_id,
+            &instruction.from,
+            &instruction.to,
+            &instruction.trx_hash,
+        ));
+    }
+    for event in &data.mail_v2_update_event_event_list {
+        replies
+            .entry(&event.id)
+            .or_insert((&event.parent_id, &event.from, &event.to, &event.trx_hash));
+    }
+
+    replies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::substreams::v1::program::{CreatemailInstruction, MailV2UpdateEventEvent};
+
+    #[test]
+    fn test_reply_as_instruction_and_event() {
+        let data = Data {
+            createmail_instruction_list: vec![CreatemailInstruction {
+                trx_hash: "tx".to_string(),
+                acct_mail: "mail-account".to_string(),
+                mail_id: "reply".to_string(),
+                parent_id: "parent".to_string(),
+                from: "bob".to_string(),
+                to: "alice".to_string(),
+                ..Default::default()
+            }],
+            mail_v2_update_event_event_list: vec![MailV2UpdateEventEvent {
+                trx_hash: "tx".to_string(),
+                id: "reply".to_string(),
+                parent_id: "parent".to_string(),
+                from: "bob".to_string(),
+                to: "alice".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let replies = replies(&data);
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies.get("reply"), Some(&("parent", "bob", "alice", "tx")));
+    }
+}
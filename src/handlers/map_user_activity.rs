@@ -0,0 +1,77 @@
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use substreams::errors::Error;
+
+use crate::pb::substreams::v1::program::Activity;
+
+/// Activity of a set of wallets, taken from the `params` as an `index` query
+/// such as `user:<pubkey> || user:<pubkey>`. The same params drive the module's
+/// `blockFilter`, so only blocks involving one of the pubkeys are processed.
+///
+/// An item is kept when one of the pubkeys is its sender, recipient, owner,
+/// authority or mailbox.
+#[substreams::handlers::map]
+pub fn map_user_activity(params: String, activity: Activity) -> Result<Activity, Error> {
+    let addresses = parse_addresses(&params)?;
+
+    let items: Vec<_> = activity
+        .items
+        .into_iter()
+        .filter(|activity| {
+            activity
+                .item
+                .as_ref()
+                .is_some_and(|item| item.involves(&addresses))
+        })
+        .collect();
+
+    substreams::log::info!("{} activity items for {} addresses", items.len(), addresses.len());
+
+    Ok(Activity { items })
+}
+
+/// Parse the `user:<pubkey>` terms of the module params into base58 pubkeys
+fn parse_addresses(params: &str) -> Result<Vec<String>, Error> {
+    let addresses = params
+        .split("||")
+        .flat_map(|term| term.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|term| !term.is_empty())
+        .map(|term| {
+            let value = term
+                .strip_prefix("user:")
+                .ok_or_else(|| Error::msg(format!("expected user:<pubkey> in params, got {:?}", term)))?;
+            Pubkey::from_str(value)
+                .map(|pubkey| pubkey.to_string())
+                .map_err(|err| Error::msg(format!("invalid pubkey {:?} in params: {}", value, err)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if addresses.is_empty() {
+        return Err(Error::msg("params must contain at least one user:<pubkey>"));
+    }
+
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_addresses() {
+        let params = "user:Mai1UbiFBUvDnE2DDRxp765sRtX792zw24cgSpcgrz1 || user:11111111111111111111111111111111\n";
+        let addresses = parse_addresses(params).unwrap();
+        assert_eq!(addresses, vec![
+            "Mai1UbiFBUvDnE2DDRxp765sRtX792zw24cgSpcgrz1".to_string(),
+            "11111111111111111111111111111111".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_addresses_rejects_invalid() {
+        assert!(parse_addresses("").is_err());
+        assert!(parse_addresses("user:not-a-pubkey").is_err());
+        assert!(parse_addresses("Mai1UbiFBUvDnE2DDRxp765sRtX792zw24cgSpcgrz1").is_err());
+    }
+}
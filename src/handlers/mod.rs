@@ -1,6 +1,91 @@
 pub mod map_program_data;
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub mod db_out;
+pub mod db_out_program_logs;
+pub mod helpers;
+pub mod index;
+pub mod map_activity;
+pub mod map_label_changes;
+pub mod map_nostr_events;
+pub mod map_notifications;
+pub mod map_program_logs;
+pub mod map_read_latency;
+pub mod map_reply_times;
+pub mod store_compute_units;
+pub mod store_daily_participants;
+pub mod store_daily_stats;
+pub mod store_error_stats;
+pub mod store_interaction_edges;
+pub mod store_interaction_first_slot;
+pub mod store_interaction_last_slot;
+pub mod store_mail_bodies;
+pub mod store_mail_first_read;
+pub mod store_mail_labels;
+pub mod store_mail_mailboxes;
+pub mod store_mail_recipients;
+pub mod store_mail_senders;
+pub mod store_mail_sent_at;
+pub mod store_mailbox_owners;
+pub mod store_mailbox_stats;
+pub mod store_nostr_accounts;
+pub mod store_read_latency_stats;
+pub mod store_registered_accounts;
+pub mod store_response_time_stats;
+pub mod store_sender_reputation;
+pub mod store_sender_window_recipients;
+pub mod store_sender_window_stats;
+pub mod store_spam_reporters;
+pub mod store_sponsor_stats;
+pub mod store_user_first_seen;
+pub mod store_user_last_active;
+pub mod store_user_stats;
+pub mod store_version_stats;
+// The handler macro dereferences the raw `params` pointer it receives from the runtime
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub mod map_abuse_alerts;
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub mod map_user_activity;
 
 // Re-export handlers for substreams
 pub use map_program_data::map_program_data;
 pub use db_out::db_out;
+pub use db_out_program_logs::db_out_program_logs;
+pub use index::index;
+pub use map_activity::map_activity;
+pub use map_label_changes::map_label_changes;
+pub use map_nostr_events::map_nostr_events;
+pub use map_notifications::map_notifications;
+pub use map_program_logs::map_program_logs;
+pub use map_read_latency::map_read_latency;
+pub use map_reply_times::map_reply_times;
+pub use store_compute_units::store_compute_units;
+pub use store_daily_participants::store_daily_participants;
+pub use store_daily_stats::store_daily_stats;
+pub use store_error_stats::store_error_stats;
+pub use store_interaction_edges::store_interaction_edges;
+pub use store_interaction_first_slot::store_interaction_first_slot;
+pub use store_interaction_last_slot::store_interaction_last_slot;
+pub use store_mail_bodies::store_mail_bodies;
+pub use store_mail_first_read::store_mail_first_read;
+pub use store_mail_labels::store_mail_labels;
+pub use store_mail_mailboxes::store_mail_mailboxes;
+pub use store_mail_recipients::store_mail_recipients;
+pub use store_mail_senders::store_mail_senders;
+pub use store_mail_sent_at::store_mail_sent_at;
+pub use store_mailbox_owners::store_mailbox_owners;
+pub use store_mailbox_stats::store_mailbox_stats;
+pub use store_nostr_accounts::store_nostr_accounts;
+pub use store_read_latency_stats::store_read_latency_stats;
+pub use store_registered_accounts::store_registered_accounts;
+pub use store_response_time_stats::store_response_time_stats;
+pub use store_sender_reputation::store_sender_reputation;
+pub use store_sender_window_recipients::store_sender_window_recipients;
+pub use store_sender_window_stats::store_sender_window_stats;
+pub use store_spam_reporters::store_spam_reporters;
+pub use store_sponsor_stats::store_sponsor_stats;
+pub use store_user_first_seen::store_user_first_seen;
+pub use store_user_last_active::store_user_last_active;
+pub use store_user_stats::store_user_stats;
+pub use store_version_stats::store_version_stats;
+pub use map_abuse_alerts::map_abuse_alerts;
+pub use map_user_activity::map_user_activity;
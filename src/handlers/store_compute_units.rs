@@ -0,0 +1,28 @@
+use substreams::pb::substreams::Clock;
+use substreams::store::{StoreAdd, StoreAddInt64, StoreNew};
+
+use crate::handlers::helpers::day_from_clock;
+use crate::pb::substreams::v1::program::Data;
+use crate::stats::COMPUTE_UNITS_HISTOGRAM;
+
+/// Daily compute unit usage per instruction kind.
+///
+/// Keys are `<day>:<kind>:count`, `<day>:<kind>:sum` and
+/// `<day>:<kind>:bucket:<index>` for the histogram used to derive percentiles.
+/// Invocations whose consumption could not be read from the logs are skipped.
+#[substreams::handlers::store]
+pub fn store_compute_units(clock: Clock, data: Data, store: StoreAddInt64) {
+    let day = day_from_clock(&clock);
+
+    for item in data.into_items() {
+        let compute_units = match item.compute_units_consumed() {
+            Some(compute_units) if compute_units > 0 => compute_units,
+            _ => continue,
+        };
+        let prefix = format!("{}:{}", day, item.kind());
+
+        store.add(0, format!("{}:count", prefix), 1);
+        store.add(0, format!("{}:sum", prefix), compute_units as i64);
+        store.add(0, format!("{}:bucket:{}", prefix, COMPUTE_UNITS_HISTOGRAM.bucket(compute_units)), 1);
+    }
+}
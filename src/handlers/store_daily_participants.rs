@@ -0,0 +1,26 @@
+use substreams::pb::substreams::Clock;
+use substreams::store::{StoreNew, StoreSet, StoreSetInt64};
+
+use crate::handlers::helpers::day_number_from_clock;
+use crate::pb::substreams::v1::program::Data;
+
+/// Last UTC day (days since the unix epoch) each sender, recipient, user and
+/// sender/recipient pair took part in a mail, keyed `sender:<address>`,
+/// `recipient:<address>`, `user:<address>` and `pair:<from>:<to>`.
+///
+/// A delta that creates a key or moves it to a new day is the first
+/// appearance of that participant in the day, which `store_daily_stats` counts
+/// as a distinct participant. Keeping one key per participant instead of one
+/// per participant and day means nothing has to be cleaned up.
+#[substreams::handlers::store]
+pub fn store_daily_participants(clock: Clock, data: Data, store: StoreSetInt64) {
+    let day = day_number_from_clock(&clock);
+
+    for (from, to) in data.mail_pairs() {
+        store.set(0, format!("sender:{}", from), &day);
+        store.set(0, format!("recipient:{}", to), &day);
+        store.set(0, format!("user:{}", from), &day);
+        store.set(0, format!("user:{}", to), &day);
+        store.set(0, format!("pair:{}:{}", from, to), &day);
+    }
+}
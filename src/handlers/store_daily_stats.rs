@@ -0,0 +1,72 @@
+use substreams::key;
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::pb::substreams::Clock;
+use substreams::store::{DeltaInt64, Deltas, StoreAdd, StoreAddInt64, StoreNew};
+
+use crate::handlers::helpers::day_from_clock;
+use crate::pb::substreams::v1::program::Data;
+
+/// Daily network counters, keyed `<day>:<metric>`:
+/// - `mails_sent`: `createmail` and `sendmail` instructions
+/// - `reads`: `updatemailreadstatus` instructions
+/// - `label_changes`: `updatemaillabel` instructions
+/// - `registrations`: `register` and `registerV2` instructions
+/// - `active_senders`, `active_recipients`, `active_users`, `unique_pairs`:
+///   participants seen for the first time that day in
+///   `store_daily_participants`
+#[substreams::handlers::store]
+pub fn store_daily_stats(clock: Clock, data: Data, participants: Deltas<DeltaInt64>, store: StoreAddInt64) {
+    let day = day_from_clock(&clock);
+
+    let counts = [
+        ("mails_sent", data.createmail_instruction_list.len() + data.sendmail_instruction_list.len()),
+        ("reads", data.updatemailreadstatus_instruction_list.len()),
+        ("label_changes", data.updatemaillabel_instruction_list.len()),
+        ("registrations", data.register_instruction_list.len() + data.register_v2_instruction_list.len()),
+    ];
+    for (metric, count) in counts {
+        if count > 0 {
+            store.add(0, format!("{}:{}", day, metric), count as i64);
+        }
+    }
+
+    for delta in participants.deltas.iter().filter(|delta| first_seen_today(delta)) {
+        let metric = match key::segment_at(&delta.key, 0) {
+            "sender" => "active_senders",
+            "recipient" => "active_recipients",
+            "user" => "active_users",
+            "pair" => "unique_pairs",
+            _ => continue,
+        };
+        store.add(0, format!("{}:{}", day, metric), 1);
+    }
+}
+
+/// Whether a `store_daily_participants` delta is the participant's first
+/// appearance of the day: a new participant, or one last seen another day
+fn first_seen_today(delta: &DeltaInt64) -> bool {
+    delta.operation == Operation::Create || delta.old_value != delta.new_value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(operation: Operation, old_value: i64, new_value: i64) -> DeltaInt64 {
+        DeltaInt64 {
+            operation,
+            ordinal: 0,
+            key: "user:alice".to_string(),
+            old_value,
+            new_value,
+        }
+    }
+
+    #[test]
+    fn test_first_seen_today() {
+        assert!(first_seen_today(&delta(Operation::Create, 0, 19_000)));
+        // Last seen days ago, whether or not blocks came in between
+        assert!(first_seen_today(&delta(Operation::Update, 18_990, 19_000)));
+        assert!(!first_seen_today(&delta(Operation::Update, 19_000, 19_000)));
+    }
+}
@@ -0,0 +1,16 @@
+use substreams::pb::substreams::Clock;
+use substreams::store::{StoreAdd, StoreAddInt64, StoreNew};
+
+use crate::handlers::helpers::day_from_clock;
+use crate::pb::substreams::v1::program::Data;
+
+/// Count failed Solmail transactions per day, instruction and error, keyed
+/// `<day>:<instruction>:<code>:<name>`
+#[substreams::handlers::store]
+pub fn store_error_stats(clock: Clock, data: Data, store: StoreAddInt64) {
+    let day = day_from_clock(&clock);
+
+    for error in &data.program_error_list {
+        store.add(0, format!("{}:{}:{}:{}", day, error.instruction, error.code, error.name), 1);
+    }
+}
@@ -0,0 +1,20 @@
+use substreams::store::{StoreAdd, StoreAddInt64, StoreNew};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Sender → recipient interaction counters, keyed `<from>:<to>:<metric>`:
+/// - `messages`: mails sent (`createmail` and `sendmail`)
+/// - `replies`: mails created with a `parent_id`
+#[substreams::handlers::store]
+pub fn store_interaction_edges(data: Data, store: StoreAddInt64) {
+    for instruction in &data.createmail_instruction_list {
+        store.add(0, format!("{}:{}:messages", instruction.from, instruction.to), 1);
+        if !instruction.parent_id.is_empty() {
+            store.add(0, format!("{}:{}:replies", instruction.from, instruction.to), 1);
+        }
+    }
+
+    for instruction in &data.sendmail_instruction_list {
+        store.add(0, format!("{}:{}:messages", instruction.from, instruction.to), 1);
+    }
+}
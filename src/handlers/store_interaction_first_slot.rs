@@ -0,0 +1,11 @@
+use substreams::store::{StoreMin, StoreMinInt64, StoreNew};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Slot of the first mail from a sender to a recipient, keyed `<from>:<to>`
+#[substreams::handlers::store]
+pub fn store_interaction_first_slot(data: Data, store: StoreMinInt64) {
+    for (from, to) in data.mail_pairs() {
+        store.min(0, format!("{}:{}", from, to), data.slot as i64);
+    }
+}
@@ -0,0 +1,11 @@
+use substreams::store::{StoreMax, StoreMaxInt64, StoreNew};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Slot of the last mail from a sender to a recipient, keyed `<from>:<to>`
+#[substreams::handlers::store]
+pub fn store_interaction_last_slot(data: Data, store: StoreMaxInt64) {
+    for (from, to) in data.mail_pairs() {
+        store.max(0, format!("{}:{}", from, to), data.slot as i64);
+    }
+}
@@ -0,0 +1,21 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsInt64};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Slot at which the body of each mail was first seen, keyed by mail id, from
+/// `sendmail`, `updatemail` and `MailV2UpdateEvent`. A `createmail` carries no
+/// body, so its mail only gets one once it is written by an update, possibly
+/// in a later block.
+#[substreams::handlers::store]
+pub fn store_mail_bodies(data: Data, store: StoreSetIfNotExistsInt64) {
+    let bodies = data
+        .sendmail_instruction_list
+        .iter()
+        .map(|instruction| (&instruction.mail_id, &instruction.body))
+        .chain(data.updatemail_instruction_list.iter().map(|instruction| (&instruction.mail_id, &instruction.body)))
+        .chain(data.mail_v2_update_event_event_list.iter().map(|event| (&event.id, &event.body)));
+
+    for (mail, _) in bodies.filter(|(mail, body)| !mail.is_empty() && !body.is_empty()) {
+        store.set_if_not_exists(0, mail, &(data.slot as i64));
+    }
+}
@@ -0,0 +1,12 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsInt64};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Block time at which each mail was first read (`MailV2ReadEvent` or
+/// `updatemailreadstatus`), keyed by mail
+#[substreams::handlers::store]
+pub fn store_mail_first_read(data: Data, store: StoreSetIfNotExistsInt64) {
+    for (mail, _, _) in data.mail_reads() {
+        store.set_if_not_exists(0, mail, &data.block_time);
+    }
+}
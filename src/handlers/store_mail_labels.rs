@@ -0,0 +1,13 @@
+use substreams::store::{StoreNew, StoreSet, StoreSetInt64};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Current label of each mail, keyed by mail. Each change is set at the
+/// ordinal of its index in `label_change_list`, so the deltas' old values
+/// give the label it replaced.
+#[substreams::handlers::store]
+pub fn store_mail_labels(data: Data, store: StoreSetInt64) {
+    for (ordinal, change) in data.label_change_list.iter().enumerate() {
+        store.set(ordinal as u64, &change.mail_id, &(change.label as i64));
+    }
+}
@@ -0,0 +1,15 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsString};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// V2 mailbox each mail was delivered to, keyed by mail, from
+/// `MailV2SendEvent`
+#[substreams::handlers::store]
+pub fn store_mail_mailboxes(data: Data, store: StoreSetIfNotExistsString) {
+    for event in &data.mail_v2_send_event_event_list {
+        if event.id.is_empty() || event.mailbox.is_empty() {
+            continue;
+        }
+        store.set_if_not_exists(0, &event.id, &event.mailbox);
+    }
+}
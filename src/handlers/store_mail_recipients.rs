@@ -0,0 +1,18 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsString};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Recipient of each mail created by `createmail` or `sendmail`, keyed by mail
+/// id
+#[substreams::handlers::store]
+pub fn store_mail_recipients(data: Data, store: StoreSetIfNotExistsString) {
+    let recipients = data
+        .createmail_instruction_list
+        .iter()
+        .map(|instruction| (&instruction.mail_id, &instruction.to))
+        .chain(data.sendmail_instruction_list.iter().map(|instruction| (&instruction.mail_id, &instruction.to)));
+
+    for (mail, to) in recipients.filter(|(mail, to)| !mail.is_empty() && !to.is_empty()) {
+        store.set_if_not_exists(0, mail, to);
+    }
+}
@@ -0,0 +1,21 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsString};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Sender of each mail, keyed by mail id (`mail_id` of `createmail` and
+/// `sendmail`, `id` of `MailSendEvent` and `MailV2SendEvent`), the identity
+/// replies reference as `parent_id`
+#[substreams::handlers::store]
+pub fn store_mail_senders(data: Data, store: StoreSetIfNotExistsString) {
+    let senders = data
+        .createmail_instruction_list
+        .iter()
+        .map(|instruction| (&instruction.mail_id, &instruction.from))
+        .chain(data.sendmail_instruction_list.iter().map(|instruction| (&instruction.mail_id, &instruction.from)))
+        .chain(data.mail_v2_send_event_event_list.iter().map(|event| (&event.id, &event.from)))
+        .chain(data.mail_send_event_event_list.iter().map(|event| (&event.id, &event.from)));
+
+    for (mail, from) in senders.filter(|(mail, from)| !mail.is_empty() && !from.is_empty()) {
+        store.set_if_not_exists(0, mail, from);
+    }
+}
@@ -0,0 +1,23 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsInt64};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Block time and slot at which each mail was sent, keyed `<mail>:time` and
+/// `<mail>:slot`. Mails are identified by their mail id (`mail_id` of
+/// `sendmail` and `createmail`, `id` of `MailSendEvent` and
+/// `MailV2SendEvent`), which replies reference as `parent_id`.
+#[substreams::handlers::store]
+pub fn store_mail_sent_at(data: Data, store: StoreSetIfNotExistsInt64) {
+    let mails = data
+        .sendmail_instruction_list
+        .iter()
+        .map(|instruction| &instruction.mail_id)
+        .chain(data.createmail_instruction_list.iter().map(|instruction| &instruction.mail_id))
+        .chain(data.mail_v2_send_event_event_list.iter().map(|event| &event.id))
+        .chain(data.mail_send_event_event_list.iter().map(|event| &event.id));
+
+    for mail in mails.filter(|mail| !mail.is_empty()) {
+        store.set_if_not_exists(0, format!("{}:time", mail), &data.block_time);
+        store.set_if_not_exists(0, format!("{}:slot", mail), &(data.slot as i64));
+    }
+}
@@ -0,0 +1,17 @@
+use substreams::store::{StoreNew, StoreSet, StoreSetString};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Authority controlling each V2 mailbox, keyed by mailbox, from
+/// `updateAccountV2`. Each update is set at the ordinal of its index in
+/// `update_account_v2_instruction_list`, so the deltas' old values give the
+/// authority it replaced.
+#[substreams::handlers::store]
+pub fn store_mailbox_owners(data: Data, store: StoreSetString) {
+    for (ordinal, instruction) in data.update_account_v2_instruction_list.iter().enumerate() {
+        if instruction.mailbox.is_empty() || instruction.acct_authority.is_empty() {
+            continue;
+        }
+        store.set(ordinal as u64, &instruction.mailbox, &instruction.acct_authority);
+    }
+}
@@ -0,0 +1,31 @@
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas, StoreAdd, StoreAddInt64, StoreGet, StoreGetString, StoreNew};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Per-mailbox counters, keyed `<mailbox>:<metric>`:
+/// - `messages`: mails delivered to the mailbox (`MailV2SendEvent`)
+/// - `read`: mails of the mailbox read at least once (`store_mail_first_read`)
+/// - `unread`: messages minus read
+#[substreams::handlers::store]
+pub fn store_mailbox_stats(
+    data: Data,
+    mail_mailboxes: StoreGetString,
+    first_reads: Deltas<DeltaInt64>,
+    store: StoreAddInt64,
+) {
+    for event in &data.mail_v2_send_event_event_list {
+        if event.id.is_empty() || event.mailbox.is_empty() {
+            continue;
+        }
+        store.add(0, format!("{}:messages", event.mailbox), 1);
+        store.add(0, format!("{}:unread", event.mailbox), 1);
+    }
+
+    for delta in first_reads.deltas.iter().filter(|delta| delta.operation == Operation::Create) {
+        if let Some(mailbox) = mail_mailboxes.get_last(&delta.key) {
+            store.add(0, format!("{}:read", mailbox), 1);
+            store.add(0, format!("{}:unread", mailbox), -1);
+        }
+    }
+}
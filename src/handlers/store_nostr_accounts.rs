@@ -0,0 +1,41 @@
+use substreams::store::{StoreNew, StoreSet, StoreSetString};
+
+use crate::nostr::{NostrKey, NostrKeyKind};
+use crate::pb::substreams::v1::program::Data;
+
+/// Registry of the nostr key of each Solmail account, keyed
+/// `account:<authority>` with the hex pubkey as value.
+///
+/// Updated by `register`, `registerV2` and `updateAccountV2`; keys that are
+/// neither hex nor npub are ignored so a bad update keeps the previous key.
+#[substreams::handlers::store]
+pub fn store_nostr_accounts(data: Data, store: StoreSetString) {
+    let registrations = data
+        .register_instruction_list
+        .iter()
+        .map(|instruction| (&instruction.acct_authority, &instruction.nostr_key))
+        .chain(
+            data.register_v2_instruction_list
+                .iter()
+                .map(|instruction| (&instruction.acct_authority, &instruction.nostr_key)),
+        )
+        .chain(
+            data.update_account_v2_instruction_list
+                .iter()
+                .map(|instruction| (&instruction.acct_authority, &instruction.nostr_key)),
+        );
+
+    for (authority, raw) in registrations {
+        let key = NostrKey::parse(raw);
+        if authority.is_empty() || key.kind == NostrKeyKind::Invalid {
+            continue;
+        }
+
+        store.set(0, account_key(authority), &key.hex);
+    }
+}
+
+/// Store key of an account's nostr pubkey
+pub fn account_key(authority: &str) -> String {
+    format!("account:{}", authority)
+}
@@ -0,0 +1,19 @@
+use substreams::pb::substreams::Clock;
+use substreams::store::{StoreAdd, StoreAddInt64, StoreNew};
+
+use crate::handlers::helpers::day_from_clock;
+use crate::pb::substreams::v1::program::MailReadLatencies;
+use crate::stats::READ_LATENCY_HISTOGRAM;
+
+/// Daily time-to-read of first reads, keyed `<day>:count`, `<day>:sum` and
+/// `<day>:bucket:<index>` for the histogram used to derive percentiles
+#[substreams::handlers::store]
+pub fn store_read_latency_stats(clock: Clock, latencies: MailReadLatencies, store: StoreAddInt64) {
+    let day = day_from_clock(&clock);
+
+    for latency in &latencies.mail_read_latency_list {
+        store.add(0, format!("{}:count", day), 1);
+        store.add(0, format!("{}:sum", day), latency.latency_seconds as i64);
+        store.add(0, format!("{}:bucket:{}", day, READ_LATENCY_HISTOGRAM.bucket(latency.latency_seconds)), 1);
+    }
+}
@@ -0,0 +1,19 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsInt64};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Slot at which each address registered a mail account (`register`,
+/// `registerV2` or `MailAccountV2RegisterEvent`), keyed by address
+#[substreams::handlers::store]
+pub fn store_registered_accounts(data: Data, store: StoreSetIfNotExistsInt64) {
+    let owners = data
+        .register_instruction_list
+        .iter()
+        .map(|instruction| &instruction.acct_authority)
+        .chain(data.register_v2_instruction_list.iter().map(|instruction| &instruction.acct_authority))
+        .chain(data.mail_account_v2_register_event_event_list.iter().map(|event| &event.owner));
+
+    for owner in owners.filter(|owner| !owner.is_empty()) {
+        store.set_if_not_exists(0, owner, &(data.slot as i64));
+    }
+}
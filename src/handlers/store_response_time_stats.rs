@@ -0,0 +1,23 @@
+use substreams::store::{StoreAdd, StoreAddInt64, StoreNew};
+
+use crate::pb::substreams::v1::program::MailReplyTimes;
+use crate::stats::RESPONSE_TIME_HISTOGRAM;
+
+/// Reply response times per replier and per replier → recipient pair.
+///
+/// Keys are `<replier>:<recipient>:<metric>`, with an empty recipient for the
+/// replier's totals. Metrics are `count`, `sum` and `bucket:<index>` for the
+/// histogram used to derive percentiles.
+#[substreams::handlers::store]
+pub fn store_response_time_stats(reply_times: MailReplyTimes, store: StoreAddInt64) {
+    for reply in &reply_times.mail_reply_time_list {
+        let bucket = RESPONSE_TIME_HISTOGRAM.bucket(reply.response_seconds);
+
+        for recipient in ["", reply.recipient.as_str()] {
+            let prefix = format!("{}:{}", reply.replier, recipient);
+            store.add(0, format!("{}:count", prefix), 1);
+            store.add(0, format!("{}:sum", prefix), reply.response_seconds as i64);
+            store.add(0, format!("{}:bucket:{}", prefix, bucket), 1);
+        }
+    }
+}
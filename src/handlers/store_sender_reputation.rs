@@ -0,0 +1,69 @@
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas, StoreAdd, StoreAddInt64, StoreGet, StoreGetString, StoreNew};
+
+use crate::idl::idl::program::types::MailLabel;
+use crate::pb::substreams::v1::program::Data;
+
+/// Per-sender spam signals, keyed `<sender>:<metric>`:
+/// - `sent`: mails sent
+/// - `spam_labels`: mails currently labelled `Spam` by their recipient,
+///   decremented when the label is changed away from `Spam`
+/// - `spam_reports`: times a mail of the sender was labelled `Spam`
+/// - `reporters`: distinct recipients who labelled a mail `Spam`
+///   (`store_spam_reporters`)
+#[substreams::handlers::store]
+pub fn store_sender_reputation(
+    data: Data,
+    mail_labels: Deltas<DeltaInt64>,
+    mail_senders: StoreGetString,
+    spam_reporters: Deltas<DeltaInt64>,
+    store: StoreAddInt64,
+) {
+    for (from, _) in data.mail_pairs() {
+        store.add(0, format!("{}:sent", from), 1);
+    }
+
+    for (sender, _, change) in spam_transitions(&data, &mail_labels, &mail_senders) {
+        store.add(0, format!("{}:spam_labels", sender), change);
+        if change > 0 {
+            store.add(0, format!("{}:spam_reports", sender), 1);
+        }
+    }
+
+    for delta in spam_reporters.deltas.iter().filter(|delta| delta.operation == Operation::Create) {
+        let sender = substreams::key::segment_at(&delta.key, 0);
+        store.add(0, format!("{}:reporters", sender), 1);
+    }
+}
+
+/// Mails labelled to or away from `Spam` in this block, as
+/// `(sender, reporter, +1 | -1)`. The previous label comes from the
+/// `store_mail_labels` deltas, set at the index of each label change. Mails
+/// whose sender is unknown are skipped.
+pub(crate) fn spam_transitions(
+    data: &Data,
+    mail_labels: &Deltas<DeltaInt64>,
+    mail_senders: &StoreGetString,
+) -> Vec<(String, String, i64)> {
+    let spam = MailLabel::Spam as i64;
+
+    mail_labels
+        .deltas
+        .iter()
+        .filter_map(|delta| {
+            let was_spam = delta.operation != Operation::Create && delta.old_value == spam;
+            let change = match (was_spam, delta.new_value == spam) {
+                (false, true) => 1,
+                (true, false) => -1,
+                _ => return None,
+            };
+            let sender = mail_senders.get_last(&delta.key)?;
+            let reporter = data
+                .label_change_list
+                .get(delta.ordinal as usize)
+                .map(|label_change| label_change.owner.clone())
+                .unwrap_or_default();
+            Some((sender, reporter, change))
+        })
+        .collect()
+}
@@ -0,0 +1,23 @@
+use substreams::store::{StoreDelete, StoreGet, StoreGetInt64, StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsInt64};
+
+use crate::abuse::window;
+use crate::pb::substreams::v1::program::Data;
+
+/// Recipients of each sender per counting window, keyed
+/// `<window>:<sender>:<recipient>`, set to 1 when the recipient had a
+/// registered mail account when first mailed in the window and 0 otherwise.
+///
+/// Only key creations matter: `store_sender_window_stats` counts them as
+/// distinct recipients. Windows no longer evaluated are dropped.
+#[substreams::handlers::store]
+pub fn store_sender_window_recipients(data: Data, registered_accounts: StoreGetInt64, store: StoreSetIfNotExistsInt64) {
+    let window = window(data.slot);
+    if window >= 2 {
+        store.delete_prefix(0, &format!("{}:", window - 2));
+    }
+
+    for (from, to) in data.mail_pairs() {
+        let registered = registered_accounts.has_last(to) as i64;
+        store.set_if_not_exists(0, format!("{}:{}:{}", window, from, to), &registered);
+    }
+}
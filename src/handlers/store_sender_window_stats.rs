@@ -0,0 +1,32 @@
+use substreams::key;
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas, StoreAdd, StoreAddInt64, StoreDelete, StoreNew};
+
+use crate::abuse::window;
+use crate::pb::substreams::v1::program::Data;
+
+/// Per-sender counters per counting window, keyed `<window>:<sender>:<metric>`:
+/// - `sends`: mails sent
+/// - `recipients`: distinct recipients (`store_sender_window_recipients`)
+/// - `unregistered`: distinct recipients without a registered mail account
+///
+/// Windows no longer evaluated are dropped.
+#[substreams::handlers::store]
+pub fn store_sender_window_stats(data: Data, window_recipients: Deltas<DeltaInt64>, store: StoreAddInt64) {
+    let window = window(data.slot);
+    if window >= 2 {
+        store.delete_prefix(0, &format!("{}:", window - 2));
+    }
+
+    for (from, _) in data.mail_pairs() {
+        store.add(0, format!("{}:{}:sends", window, from), 1);
+    }
+
+    for delta in window_recipients.deltas.iter().filter(|delta| delta.operation == Operation::Create) {
+        let sender = key::segment_at(&delta.key, 1);
+        store.add(0, format!("{}:{}:recipients", window, sender), 1);
+        if delta.new_value == 0 {
+            store.add(0, format!("{}:{}:unregistered", window, sender), 1);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use substreams::store::{DeltaInt64, Deltas, StoreGet, StoreGetString, StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsInt64};
+
+use crate::handlers::store_sender_reputation::spam_transitions;
+use crate::pb::substreams::v1::program::Data;
+
+/// Recipients who labelled a sender's mail as spam, keyed
+/// `<sender>:<reporter>` with the slot of the first report
+#[substreams::handlers::store]
+pub fn store_spam_reporters(
+    data: Data,
+    mail_labels: Deltas<DeltaInt64>,
+    mail_senders: StoreGetString,
+    store: StoreSetIfNotExistsInt64,
+) {
+    for (sender, reporter, _) in spam_transitions(&data, &mail_labels, &mail_senders) {
+        if reporter.is_empty() || sender == reporter {
+            continue;
+        }
+        store.set_if_not_exists(0, format!("{}:{}", sender, reporter), &(data.slot as i64));
+    }
+}
@@ -0,0 +1,28 @@
+use substreams::pb::substreams::Clock;
+use substreams::store::{StoreAdd, StoreAddInt64, StoreNew};
+
+use crate::handlers::helpers::day_from_clock;
+use crate::pb::substreams::v1::program::Data;
+
+/// Count sponsored sends (`createmail` and `sendmail` paid by someone other
+/// than the authority) per sponsor per day, keyed `<day>:<sponsor>`
+#[substreams::handlers::store]
+pub fn store_sponsor_stats(clock: Clock, data: Data, store: StoreAddInt64) {
+    let day = day_from_clock(&clock);
+
+    let sponsors = data
+        .createmail_instruction_list
+        .iter()
+        .filter(|instruction| instruction.sponsored)
+        .map(|instruction| &instruction.fee_payer)
+        .chain(
+            data.sendmail_instruction_list
+                .iter()
+                .filter(|instruction| instruction.sponsored)
+                .map(|instruction| &instruction.fee_payer),
+        );
+
+    for sponsor in sponsors {
+        store.add(0, format!("{}:{}", day, sponsor), 1);
+    }
+}
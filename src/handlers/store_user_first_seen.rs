@@ -0,0 +1,12 @@
+use substreams::store::{StoreMin, StoreMinInt64, StoreNew};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Slot at which each address first appears in a Solmail instruction, in any
+/// role, keyed by address
+#[substreams::handlers::store]
+pub fn store_user_first_seen(data: Data, store: StoreMinInt64) {
+    for address in data.instruction_addresses() {
+        store.min(0, address, data.slot as i64);
+    }
+}
@@ -0,0 +1,12 @@
+use substreams::store::{StoreMax, StoreMaxInt64, StoreNew};
+
+use crate::pb::substreams::v1::program::Data;
+
+/// Last slot at which each address signed a Solmail instruction, keyed by
+/// address
+#[substreams::handlers::store]
+pub fn store_user_last_active(data: Data, store: StoreMaxInt64) {
+    for address in data.instruction_authorities() {
+        store.max(0, address, data.slot as i64);
+    }
+}
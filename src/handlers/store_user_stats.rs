@@ -0,0 +1,89 @@
+use substreams::pb::substreams::store_delta::Operation;
+use substreams::store::{DeltaInt64, Deltas, StoreAdd, StoreAddInt64, StoreGet, StoreGetString, StoreNew};
+
+use crate::idl::idl::program::types::MailLabel;
+use crate::pb::substreams::v1::program::Data;
+
+/// Per-user counters, keyed `<address>:<metric>`:
+/// - `sent` / `received`: mails from / to the address
+/// - `read`: mails received by the address read at least once
+///   (`store_mail_first_read`, from `updatemailreadstatus` or
+///   `MailV2ReadEvent`)
+/// - `unread`: mails received minus mails read
+/// - `trashed` / `spam`: label changes to `Trash` / `Spam` by the address
+///
+/// Reads are only counted for mails whose recipient is known from
+/// `store_mail_recipients`, i.e. mails already counted as received, so
+/// `unread` never goes negative.
+#[substreams::handlers::store]
+pub fn store_user_stats(
+    data: Data,
+    first_reads: Deltas<DeltaInt64>,
+    mail_recipients: StoreGetString,
+    store: StoreAddInt64,
+) {
+    for (from, to) in data.mail_pairs() {
+        store.add(0, format!("{}:sent", from), 1);
+        store.add(0, format!("{}:received", to), 1);
+        store.add(0, format!("{}:unread", to), 1);
+    }
+
+    for recipient in read_recipients(&first_reads, |mail| mail_recipients.get_last(mail)) {
+        store.add(0, format!("{}:read", recipient), 1);
+        store.add(0, format!("{}:unread", recipient), -1);
+    }
+
+    for instruction in &data.updatemaillabel_instruction_list {
+        let metric = match instruction.label {
+            label if label == MailLabel::Trash as u64 => "trashed",
+            label if label == MailLabel::Spam as u64 => "spam",
+            _ => continue,
+        };
+        store.add(0, format!("{}:{}", instruction.acct_authority, metric), 1);
+    }
+}
+
+/// Recipients of the mails read for the first time in the block, resolving
+/// each mail's recipient with `recipient`; mails of unknown recipient are
+/// skipped
+fn read_recipients<F>(first_reads: &Deltas<DeltaInt64>, recipient: F) -> Vec<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    first_reads
+        .deltas
+        .iter()
+        .filter(|delta| delta.operation == Operation::Create)
+        .filter_map(|delta| recipient(&delta.key))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(operation: Operation, mail: &str) -> DeltaInt64 {
+        DeltaInt64 {
+            operation,
+            ordinal: 0,
+            key: mail.to_string(),
+            old_value: 0,
+            new_value: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_read_recipients() {
+        // `mail-1` is read through both `updatemailreadstatus` and its
+        // `MailV2ReadEvent`, which `store_mail_first_read` folds into one key
+        let first_reads = Deltas {
+            deltas: vec![
+                delta(Operation::Create, "mail-1"),
+                delta(Operation::Create, "unknown"),
+            ],
+        };
+
+        let recipients = read_recipients(&first_reads, |mail| (mail == "mail-1").then(|| "bob".to_string()));
+        assert_eq!(recipients, vec!["bob".to_string()]);
+    }
+}
@@ -0,0 +1,43 @@
+use substreams::pb::substreams::Clock;
+use substreams::store::{StoreAdd, StoreAddInt64, StoreNew};
+
+use crate::handlers::helpers::day_from_clock;
+use crate::pb::substreams::v1::program::Data;
+use crate::validation::expected_encryption_lengths;
+
+/// Count new mails (`createmail` and `sendmail`) per day and encryption
+/// `version`, keyed `<day>:<version>:<metric>`:
+/// - `mails`: mails written with the version
+/// - `unknown`: mails of a version missing from
+///   [ENCRYPTION_VERSIONS](crate::validation::ENCRYPTION_VERSIONS)
+/// - `mismatched`: mails of a known version whose salt or IV length doesn't
+///   match what the version implies
+#[substreams::handlers::store]
+pub fn store_version_stats(clock: Clock, data: Data, store: StoreAddInt64) {
+    let day = day_from_clock(&clock);
+
+    let mails = data
+        .createmail_instruction_list
+        .iter()
+        .map(|i| (&i.version, &i.salt, &i.iv))
+        .chain(data.sendmail_instruction_list.iter().map(|i| (&i.version, &i.salt, &i.iv)));
+
+    for (version, salt, iv) in mails {
+        let prefix = format!("{}:{}", day, key_segment(version));
+
+        store.add(0, format!("{}:mails", prefix), 1);
+        match expected_encryption_lengths(version) {
+            None => store.add(0, format!("{}:unknown", prefix), 1),
+            Some((salt_len, iv_len)) if salt.len() != salt_len || iv.len() != iv_len => {
+                store.add(0, format!("{}:mismatched", prefix), 1)
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Versions are free-form strings written by clients; keep them from adding
+/// key segments
+fn key_segment(value: &str) -> String {
+    value.replace(':', "_")
+}
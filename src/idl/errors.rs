@@ -0,0 +1,23 @@
+/// Custom errors declared in the program IDL: code, name and message
+pub const PROGRAM_ERRORS: &[(u32, &str, &str)] = &[
+    (6000, "InvalidInstruction", "Invalid instruction"),
+    (6001, "InvalidBody", "The body of your email is too long. The max is 512 chars"),
+    (6002, "InvalidSubject", "The subject of your email is too long. The max is 40 chars"),
+    (6003, "InvalidSalt", "The salt should be exactly 16 chars"),
+    (6004, "InvalidIv", "The IV should be exactly 32 chars"),
+    (6005, "InvalidDiffie", "The diffie publickey should be exactly 64 chars"),
+    (6006, "InvalidDestination", "From and to address should not be same"),
+    (6007, "InvalidSource", "Signer and from address should be same"),
+    (6008, "Unauthorized", "Unauthorized access"),
+    (6009, "UnauthorizedSigner", "Unauthorized signer"),
+    (6010, "InvalidLabel", "Invalid label"),
+    (6011, "InvalidMailAccount", "Invalid mailaccount"),
+];
+
+/// Name and message of a custom program error
+pub fn program_error(code: u32) -> Option<(&'static str, &'static str)> {
+    PROGRAM_ERRORS
+        .iter()
+        .find(|(error_code, _, _)| *error_code == code)
+        .map(|(_, name, message)| (*name, *message))
+}
@@ -1,3 +1,6 @@
+pub mod errors;
+
+#[allow(clippy::module_inception, clippy::too_many_arguments)]
 pub mod idl {
     use anchor_lang::declare_program;
 
@@ -1,11 +1,17 @@
 mod idl;
+#[allow(dead_code)]
 mod pb;
 mod types;
 mod deserializers;
 mod handlers;
+mod activity;
+mod stats;
+mod abuse;
+mod nostr;
+mod validation;
 
 // Re-export main handlers for substreams
-pub use handlers::{map_program_data, db_out};
+pub use handlers::{map_program_data, db_out, db_out_program_logs, index, map_abuse_alerts, map_activity, map_label_changes, map_nostr_events, map_notifications, map_program_logs, map_read_latency, map_reply_times, map_user_activity, store_compute_units, store_daily_participants, store_daily_stats, store_error_stats, store_interaction_edges, store_interaction_first_slot, store_interaction_last_slot, store_mail_bodies, store_mail_first_read, store_mail_labels, store_mail_mailboxes, store_mail_recipients, store_mail_senders, store_mail_sent_at, store_mailbox_owners, store_mailbox_stats, store_nostr_accounts, store_read_latency_stats, store_registered_accounts, store_response_time_stats, store_sender_reputation, store_sender_window_recipients, store_sender_window_stats, store_spam_reporters, store_sponsor_stats, store_user_first_seen, store_user_last_active, store_user_stats, store_version_stats};
 
 // Constants
 pub const PROGRAM_ID: &str = "Mai1UbiFBUvDnE2DDRxp765sRtX792zw24cgSpcgrz1";
@@ -0,0 +1,216 @@
+//! Classification and normalization of the nostr keys accounts register.
+//!
+//! The program stores `nostr_key` as a free-form string. Clients write either
+//! the 64-char hex x-only pubkey or its NIP-19 `npub` bech32 encoding; both
+//! forms are derived here so the key can be looked up by either.
+
+const NPUB_HRP: &str = "npub";
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+/// Form in which a nostr key was registered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NostrKeyKind {
+    Hex,
+    Npub,
+    Invalid,
+}
+
+impl NostrKeyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NostrKeyKind::Hex => "hex",
+            NostrKeyKind::Npub => "npub",
+            NostrKeyKind::Invalid => "invalid",
+        }
+    }
+}
+
+/// A registered nostr key with its lowercase hex and npub forms, both empty
+/// when the key is invalid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NostrKey {
+    pub kind: NostrKeyKind,
+    pub hex: String,
+    pub npub: String,
+}
+
+impl NostrKey {
+    /// Classify a raw `nostr_key` and derive its normalized forms
+    pub fn parse(raw: &str) -> Self {
+        let Some(bytes) = parse_pubkey(raw) else {
+            return NostrKey {
+                kind: NostrKeyKind::Invalid,
+                hex: String::new(),
+                npub: String::new(),
+            };
+        };
+
+        let kind = if decode_hex(raw.trim()).is_some() {
+            NostrKeyKind::Hex
+        } else {
+            NostrKeyKind::Npub
+        };
+        NostrKey {
+            kind,
+            hex: encode_hex(&bytes),
+            npub: encode_npub(&bytes),
+        }
+    }
+}
+
+/// 32-byte x-only pubkey of a nostr key written as hex or npub
+pub fn parse_pubkey(raw: &str) -> Option<[u8; 32]> {
+    let raw = raw.trim();
+    decode_hex(raw).or_else(|| decode_npub(raw))
+}
+
+/// 32-byte key from its 64-char hex form, in either case
+fn decode_hex(value: &str) -> Option<[u8; 32]> {
+    if value.len() != 64 || !value.is_ascii() {
+        return None;
+    }
+
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 32-byte key from its bech32 `npub1…` form
+fn decode_npub(value: &str) -> Option<[u8; 32]> {
+    // bech32 strings are all lowercase or all uppercase
+    if value.chars().any(|c| c.is_ascii_lowercase()) && value.chars().any(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let value = value.to_ascii_lowercase();
+
+    let separator = value.rfind('1')?;
+    let (hrp, data) = (&value[..separator], &value[separator + 1..]);
+    if hrp != NPUB_HRP || data.len() < 6 {
+        return None;
+    }
+
+    let values = data
+        .bytes()
+        .map(|c| CHARSET.iter().position(|&d| d == c).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()?;
+
+    let mut checked = hrp_expand(hrp);
+    checked.extend_from_slice(&values);
+    if polymod(&checked) != 1 {
+        return None;
+    }
+
+    let bytes = convert_bits(&values[..values.len() - 6], 5, 8, false)?;
+    bytes.try_into().ok()
+}
+
+fn encode_npub(bytes: &[u8]) -> String {
+    let mut values = convert_bits(bytes, 8, 5, true).unwrap_or_default();
+
+    let mut checked = hrp_expand(NPUB_HRP);
+    checked.extend_from_slice(&values);
+    checked.extend_from_slice(&[0; 6]);
+    let checksum = polymod(&checked) ^ 1;
+    values.extend((0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8));
+
+    let mut npub = format!("{}1", NPUB_HRP);
+    npub.extend(values.iter().map(|&v| CHARSET[v as usize] as char));
+    npub
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|c| c & 31));
+    expanded
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut checksum: u32 = 1;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+/// Regroup `data` from `from`-bit to `to`-bit values
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1 << to) - 1;
+    let mut out = Vec::new();
+
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return None;
+        }
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return None;
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example key from NIP-19
+    const HEX: &str = "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e";
+    const NPUB: &str = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+
+    #[test]
+    fn test_hex_key() {
+        let key = NostrKey::parse(&HEX.to_uppercase());
+        assert_eq!(key.kind, NostrKeyKind::Hex);
+        assert_eq!(key.hex, HEX);
+        assert_eq!(key.npub, NPUB);
+    }
+
+    #[test]
+    fn test_npub_key() {
+        let key = NostrKey::parse(NPUB);
+        assert_eq!(key.kind, NostrKeyKind::Npub);
+        assert_eq!(key.hex, HEX);
+        assert_eq!(key.npub, NPUB);
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        // Broken checksum, wrong prefix, short hex, free text
+        let mut broken = NPUB.to_string();
+        broken.pop();
+        broken.push('q');
+
+        for raw in [broken.as_str(), "nsec10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg", &HEX[..62], "hello", ""] {
+            let key = NostrKey::parse(raw);
+            assert_eq!(key.kind, NostrKeyKind::Invalid, "{}", raw);
+            assert!(key.hex.is_empty() && key.npub.is_empty());
+        }
+    }
+}
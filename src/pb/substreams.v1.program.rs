@@ -45,6 +45,16 @@ pub struct Data {
     pub sendmail_instruction_list: ::prost::alloc::vec::Vec<SendmailInstruction>,
     #[prost(message, repeated, tag = "15")]
     pub register_instruction_list: ::prost::alloc::vec::Vec<RegisterInstruction>,
+    #[prost(message, repeated, tag = "16")]
+    pub transaction_meta_list: ::prost::alloc::vec::Vec<TransactionMeta>,
+    #[prost(message, repeated, tag = "17")]
+    pub program_error_list: ::prost::alloc::vec::Vec<ProgramError>,
+    #[prost(uint64, tag = "18")]
+    pub slot: u64,
+    #[prost(int64, tag = "19")]
+    pub block_time: i64,
+    #[prost(message, repeated, tag = "20")]
+    pub label_change_list: ::prost::alloc::vec::Vec<LabelChange>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MailSendEventEvent {
@@ -163,6 +173,16 @@ pub struct CreatemailInstruction {
     pub acct_authority: ::prost::alloc::string::String,
     #[prost(string, tag = "12")]
     pub acct_system_program: ::prost::alloc::string::String,
+    #[prost(string, tag = "13")]
+    pub fee_payer: ::prost::alloc::string::String,
+    #[prost(bool, tag = "14")]
+    pub sponsored: bool,
+    #[prost(uint64, tag = "15")]
+    pub compute_units_consumed: u64,
+    #[prost(string, repeated, tag = "16")]
+    pub violations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "17")]
+    pub mail_id: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdatemailInstruction {
@@ -176,6 +196,16 @@ pub struct UpdatemailInstruction {
     pub acct_authority: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
     pub acct_system_program: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub fee_payer: ::prost::alloc::string::String,
+    #[prost(bool, tag = "7")]
+    pub sponsored: bool,
+    #[prost(uint64, tag = "8")]
+    pub compute_units_consumed: u64,
+    #[prost(string, repeated, tag = "9")]
+    pub violations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "10")]
+    pub mail_id: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdatemailreadstatusInstruction {
@@ -187,6 +217,14 @@ pub struct UpdatemailreadstatusInstruction {
     pub acct_authority: ::prost::alloc::string::String,
     #[prost(string, tag = "4")]
     pub acct_system_program: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub fee_payer: ::prost::alloc::string::String,
+    #[prost(bool, tag = "6")]
+    pub sponsored: bool,
+    #[prost(uint64, tag = "7")]
+    pub compute_units_consumed: u64,
+    #[prost(string, tag = "8")]
+    pub mail_id: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdatemaillabelInstruction {
@@ -200,6 +238,16 @@ pub struct UpdatemaillabelInstruction {
     pub acct_authority: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
     pub acct_system_program: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub fee_payer: ::prost::alloc::string::String,
+    #[prost(bool, tag = "7")]
+    pub sponsored: bool,
+    #[prost(uint64, tag = "8")]
+    pub compute_units_consumed: u64,
+    #[prost(string, tag = "9")]
+    pub mail_id: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "10")]
+    pub violations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RegisterV2Instruction {
@@ -213,6 +261,16 @@ pub struct RegisterV2Instruction {
     pub acct_authority: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
     pub acct_system_program: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "6")]
+    pub compute_units_consumed: u64,
+    #[prost(string, repeated, tag = "7")]
+    pub violations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "8")]
+    pub nostr_key_kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub nostr_key_hex: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub nostr_npub: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateAccountV2Instruction {
@@ -226,6 +284,16 @@ pub struct UpdateAccountV2Instruction {
     pub acct_mail_account_v2: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
     pub acct_authority: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "6")]
+    pub compute_units_consumed: u64,
+    #[prost(string, repeated, tag = "7")]
+    pub violations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "8")]
+    pub nostr_key_kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub nostr_key_hex: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub nostr_npub: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendmailInstruction {
@@ -251,6 +319,16 @@ pub struct SendmailInstruction {
     pub acct_authority: ::prost::alloc::string::String,
     #[prost(string, tag = "11")]
     pub acct_system_program: ::prost::alloc::string::String,
+    #[prost(string, tag = "12")]
+    pub fee_payer: ::prost::alloc::string::String,
+    #[prost(bool, tag = "13")]
+    pub sponsored: bool,
+    #[prost(uint64, tag = "14")]
+    pub compute_units_consumed: u64,
+    #[prost(string, repeated, tag = "15")]
+    pub violations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "16")]
+    pub mail_id: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RegisterInstruction {
@@ -264,4 +342,292 @@ pub struct RegisterInstruction {
     pub acct_authority: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
     pub acct_system_program: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "6")]
+    pub compute_units_consumed: u64,
+    #[prost(string, repeated, tag = "7")]
+    pub violations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "8")]
+    pub nostr_key_kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub nostr_key_hex: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub nostr_npub: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransactionMeta {
+    #[prost(string, tag = "1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub slot: u64,
+    #[prost(string, tag = "3")]
+    pub fee_payer: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "4")]
+    pub signers: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint64, tag = "5")]
+    pub fee: u64,
+    #[prost(uint64, tag = "6")]
+    pub compute_units_consumed: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProgramError {
+    #[prost(string, tag = "1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub slot: u64,
+    #[prost(uint32, tag = "3")]
+    pub tx_index: u32,
+    #[prost(uint32, tag = "4")]
+    pub instruction_index: u32,
+    #[prost(string, tag = "5")]
+    pub instruction: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "6")]
+    pub code: u32,
+    #[prost(string, tag = "7")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub message: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub signer: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Activity {
+    #[prost(message, repeated, tag = "1")]
+    pub items: ::prost::alloc::vec::Vec<ActivityItem>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ActivityItem {
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    #[prost(int64, tag = "2")]
+    pub block_time: i64,
+    #[prost(string, tag = "3")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "4")]
+    pub tx_index: u32,
+    #[prost(uint32, tag = "5")]
+    pub ordinal: u32,
+    #[prost(string, tag = "6")]
+    pub signer: ::prost::alloc::string::String,
+    #[prost(string, tag = "7")]
+    pub program_id: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "8")]
+    pub invocation_index: u32,
+    #[prost(
+        oneof = "activity_item::Item",
+        tags = "10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24"
+    )]
+    pub item: ::core::option::Option<activity_item::Item>,
+}
+/// Nested message and enum types in `ActivityItem`.
+pub mod activity_item {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Item {
+        #[prost(message, tag = "10")]
+        MailSendEvent(super::MailSendEventEvent),
+        #[prost(message, tag = "11")]
+        MailV2SendEvent(super::MailV2SendEventEvent),
+        #[prost(message, tag = "12")]
+        MailV2UpdateEvent(super::MailV2UpdateEventEvent),
+        #[prost(message, tag = "13")]
+        MailV2ReadEvent(super::MailV2ReadEventEvent),
+        #[prost(message, tag = "14")]
+        MailV2UpdateLabelEvent(super::MailV2UpdateLabelEventEvent),
+        #[prost(message, tag = "15")]
+        MailAccountV2RegisterEvent(super::MailAccountV2RegisterEventEvent),
+        #[prost(message, tag = "16")]
+        MailAccountV2UpdateEvent(super::MailAccountV2UpdateEventEvent),
+        #[prost(message, tag = "17")]
+        CreatemailInstruction(super::CreatemailInstruction),
+        #[prost(message, tag = "18")]
+        UpdatemailInstruction(super::UpdatemailInstruction),
+        #[prost(message, tag = "19")]
+        UpdatemailreadstatusInstruction(super::UpdatemailreadstatusInstruction),
+        #[prost(message, tag = "20")]
+        UpdatemaillabelInstruction(super::UpdatemaillabelInstruction),
+        #[prost(message, tag = "21")]
+        RegisterV2Instruction(super::RegisterV2Instruction),
+        #[prost(message, tag = "22")]
+        UpdateAccountV2Instruction(super::UpdateAccountV2Instruction),
+        #[prost(message, tag = "23")]
+        SendmailInstruction(super::SendmailInstruction),
+        #[prost(message, tag = "24")]
+        RegisterInstruction(super::RegisterInstruction),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProgramLogs {
+    #[prost(message, repeated, tag = "1")]
+    pub program_log_list: ::prost::alloc::vec::Vec<ProgramLog>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProgramLog {
+    #[prost(string, tag = "1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub slot: u64,
+    #[prost(uint32, tag = "3")]
+    pub tx_index: u32,
+    #[prost(uint32, tag = "4")]
+    pub invocation_index: u32,
+    #[prost(uint32, tag = "5")]
+    pub depth: u32,
+    #[prost(uint32, tag = "6")]
+    pub ordinal: u32,
+    #[prost(string, tag = "7")]
+    pub message: ::prost::alloc::string::String,
+    #[prost(bool, tag = "8")]
+    pub failed: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NostrEvents {
+    #[prost(message, repeated, tag = "1")]
+    pub events: ::prost::alloc::vec::Vec<NostrEvent>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NostrEvent {
+    #[prost(string, tag = "1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub slot: u64,
+    #[prost(uint32, tag = "3")]
+    pub ordinal: u32,
+    #[prost(string, tag = "4")]
+    pub source: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub mail_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub pubkey: ::prost::alloc::string::String,
+    #[prost(int64, tag = "7")]
+    pub created_at: i64,
+    #[prost(uint32, tag = "8")]
+    pub kind: u32,
+    #[prost(message, repeated, tag = "9")]
+    pub tags: ::prost::alloc::vec::Vec<NostrTag>,
+    #[prost(string, tag = "10")]
+    pub content: ::prost::alloc::string::String,
+    #[prost(string, tag = "11")]
+    pub json: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NostrTag {
+    #[prost(string, repeated, tag = "1")]
+    pub values: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MailReadLatencies {
+    #[prost(message, repeated, tag = "1")]
+    pub mail_read_latency_list: ::prost::alloc::vec::Vec<MailReadLatency>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MailReadLatency {
+    #[prost(string, tag = "1")]
+    pub mail_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub reader: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub sent_slot: u64,
+    #[prost(int64, tag = "5")]
+    pub sent_at: i64,
+    #[prost(uint64, tag = "6")]
+    pub read_slot: u64,
+    #[prost(int64, tag = "7")]
+    pub read_at: i64,
+    #[prost(uint64, tag = "8")]
+    pub latency_seconds: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MailReplyTimes {
+    #[prost(message, repeated, tag = "1")]
+    pub mail_reply_time_list: ::prost::alloc::vec::Vec<MailReplyTime>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MailReplyTime {
+    #[prost(string, tag = "1")]
+    pub reply_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub parent_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub replier: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub recipient: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "6")]
+    pub slot: u64,
+    #[prost(int64, tag = "7")]
+    pub parent_sent_at: i64,
+    #[prost(int64, tag = "8")]
+    pub replied_at: i64,
+    #[prost(uint64, tag = "9")]
+    pub response_seconds: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LabelChanges {
+    #[prost(message, repeated, tag = "1")]
+    pub label_change_list: ::prost::alloc::vec::Vec<LabelChange>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LabelChange {
+    #[prost(string, tag = "1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub mail_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub label: u64,
+    #[prost(uint64, tag = "5")]
+    pub slot: u64,
+    #[prost(uint64, optional, tag = "6")]
+    pub previous_label: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AbuseAlerts {
+    #[prost(message, repeated, tag = "1")]
+    pub abuse_alert_list: ::prost::alloc::vec::Vec<AbuseAlert>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AbuseAlert {
+    #[prost(string, tag = "1")]
+    pub sender: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub slot: u64,
+    #[prost(uint64, tag = "4")]
+    pub window_start_slot: u64,
+    #[prost(uint64, tag = "5")]
+    pub sends: u64,
+    #[prost(uint64, tag = "6")]
+    pub recipients: u64,
+    #[prost(uint64, tag = "7")]
+    pub unregistered_recipients: u64,
+    #[prost(uint32, tag = "8")]
+    pub unregistered_pct: u32,
+    #[prost(string, repeated, tag = "9")]
+    pub reasons: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Notifications {
+    #[prost(message, repeated, tag = "1")]
+    pub notifications: ::prost::alloc::vec::Vec<Notification>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Notification {
+    #[prost(string, tag = "1")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub recipient: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub mail_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub sender: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "6")]
+    pub slot: u64,
+    #[prost(int64, tag = "7")]
+    pub block_time: i64,
 }
@@ -0,0 +1,154 @@
+/// Fixed-width histogram kept in an `add` store, used to approximate
+/// percentiles of values that cannot all be kept around.
+///
+/// Values are counted in `buckets` buckets of `width`; the last bucket also
+/// holds every value above the covered range.
+pub struct Histogram {
+    pub width: u64,
+    pub buckets: usize,
+}
+
+/// Compute units per invocation, by steps of 2,000 up to 200,000
+pub const COMPUTE_UNITS_HISTOGRAM: Histogram = Histogram::new(2_000, 101);
+
+impl Histogram {
+    pub const fn new(width: u64, buckets: usize) -> Self {
+        Histogram { width, buckets }
+    }
+
+    /// Index of the bucket counting `value`
+    pub fn bucket(&self, value: u64) -> usize {
+        ((value / self.width) as usize).min(self.buckets - 1)
+    }
+
+    /// Approximate percentile (`quantile` between 0 and 1) of the counted
+    /// values: the upper bound of the bucket in which the cumulative count
+    /// reaches the quantile's rank. Returns 0 when nothing was counted.
+    pub fn percentile(&self, counts: &[i64], quantile: f64) -> u64 {
+        percentile_bucket(counts, quantile)
+            .map(|index| (index as u64 + 1) * self.width)
+            .unwrap_or_default()
+    }
+}
+
+/// Histogram whose buckets grow geometrically, `steps` buckets per doubling,
+/// for values spanning several orders of magnitude. The relative error of a
+/// percentile is bounded by the bucket ratio, `2^(1/steps)`.
+///
+/// The last bucket also holds every value above the covered range.
+pub struct LogHistogram {
+    pub steps: u32,
+    pub buckets: usize,
+}
+
+/// Seconds from send to first read, 4 buckets per doubling up to ~194 days
+pub const READ_LATENCY_HISTOGRAM: LogHistogram = LogHistogram::new(4, 96);
+
+/// Seconds from a mail to its reply, same scale as [READ_LATENCY_HISTOGRAM]
+pub const RESPONSE_TIME_HISTOGRAM: LogHistogram = LogHistogram::new(4, 96);
+
+impl LogHistogram {
+    pub const fn new(steps: u32, buckets: usize) -> Self {
+        LogHistogram { steps, buckets }
+    }
+
+    /// Index of the bucket counting `value`
+    pub fn bucket(&self, value: u64) -> usize {
+        let index = ((value as f64 + 1.0).log2() * self.steps as f64).floor() as usize;
+        index.min(self.buckets - 1)
+    }
+
+    /// Largest value counted in the bucket at `index`
+    pub fn upper_bound(&self, index: usize) -> u64 {
+        (2f64.powf((index + 1) as f64 / self.steps as f64) - 1.0).ceil() as u64 - 1
+    }
+
+    /// Approximate percentile of the counted values, see
+    /// [Histogram::percentile]
+    pub fn percentile(&self, counts: &[i64], quantile: f64) -> u64 {
+        percentile_bucket(counts, quantile)
+            .map(|index| self.upper_bound(index))
+            .unwrap_or_default()
+    }
+}
+
+/// Index of the bucket in which the cumulative count reaches the rank of
+/// `quantile`, `None` when nothing was counted
+fn percentile_bucket(counts: &[i64], quantile: f64) -> Option<usize> {
+    let total: i64 = counts.iter().sum();
+    if total <= 0 {
+        return None;
+    }
+
+    let rank = ((quantile * total as f64).ceil() as i64).max(1);
+    let mut cumulative = 0;
+    for (index, count) in counts.iter().enumerate() {
+        cumulative += count;
+        if cumulative >= rank {
+            return Some(index);
+        }
+    }
+
+    Some(counts.len() - 1)
+}
+
+/// Sender reputation between 0 and 1: the share of the sender's mails that
+/// are not currently labelled as spam, smoothed with one good and one spam
+/// mail so that new senders start at 0.5 and few reports move them less.
+pub fn sender_reputation(sent: i64, spam_labels: i64) -> f64 {
+    let sent = sent.max(0);
+    let spam = spam_labels.clamp(0, sent);
+    (sent - spam + 1) as f64 / (sent + 2) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket() {
+        let histogram = Histogram::new(10, 5);
+        assert_eq!(histogram.bucket(0), 0);
+        assert_eq!(histogram.bucket(19), 1);
+        assert_eq!(histogram.bucket(1_000), 4);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let histogram = Histogram::new(10, 5);
+        let counts = [1, 2, 5, 1, 1];
+        assert_eq!(histogram.percentile(&counts, 0.5), 30);
+        assert_eq!(histogram.percentile(&counts, 0.1), 10);
+        assert_eq!(histogram.percentile(&counts, 0.99), 50);
+        assert_eq!(histogram.percentile(&[0, 0, 0, 0, 0], 0.5), 0);
+    }
+
+    #[test]
+    fn test_log_histogram() {
+        let histogram = LogHistogram::new(1, 10);
+        assert_eq!(histogram.bucket(0), 0);
+        assert_eq!(histogram.bucket(1), 1);
+        assert_eq!(histogram.bucket(2), 1);
+        assert_eq!(histogram.bucket(3), 2);
+        assert_eq!(histogram.bucket(1_000_000), 9);
+
+        // Every value lands in the bucket whose upper bound covers it
+        for value in 0..2_000 {
+            let bucket = READ_LATENCY_HISTOGRAM.bucket(value);
+            assert!(value <= READ_LATENCY_HISTOGRAM.upper_bound(bucket), "{}", value);
+            assert!(bucket == 0 || value > READ_LATENCY_HISTOGRAM.upper_bound(bucket - 1), "{}", value);
+        }
+
+        assert_eq!(histogram.percentile(&[0, 1, 1, 1, 0, 0, 0, 0, 0, 0], 0.5), 6);
+        assert_eq!(histogram.percentile(&[0; 10], 0.5), 0);
+    }
+
+    #[test]
+    fn test_sender_reputation() {
+        assert_eq!(sender_reputation(0, 0), 0.5);
+        assert_eq!(sender_reputation(8, 0), 0.9);
+        assert_eq!(sender_reputation(8, 8), 0.1);
+        // Labels on mails sent before the first indexed block
+        assert_eq!(sender_reputation(2, 5), 0.25);
+    }
+}
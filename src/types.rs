@@ -87,7 +87,7 @@ pub struct UpdatemailreadstatusCustom {
 
 #[derive(Debug, Clone)]
 pub struct UpdatemaillabelCustom {
-    pub label: u32,
+    pub label: u8,
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,226 @@
+//! Checks of decoded payloads against the limits documented by the program's
+//! custom errors.
+//!
+//! A violation is reported under the name of the IDL error the program raises
+//! for it (e.g. `InvalidSubject`). The program rejects such payloads today, so
+//! a violation in a successful transaction means either the limits changed in
+//! an upgrade or the data predates them.
+
+use crate::idl::idl::program::types::MailLabel;
+use crate::types::*;
+
+/// Longest subject accepted by the program
+pub const MAX_SUBJECT_LEN: usize = 40;
+/// Longest body accepted by the program
+pub const MAX_BODY_LEN: usize = 512;
+/// Exact length of the encryption salt
+pub const SALT_LEN: usize = 16;
+/// Exact length of the encryption IV
+pub const IV_LEN: usize = 32;
+/// Exact length of the diffie public key registered with an account
+pub const DIFFIE_KEY_LEN: usize = 64;
+
+/// Salt and IV lengths of each known encryption scheme version, as written by
+/// clients in `version`.
+///
+/// `1.0` is the version the GraphQL API assumes for mails without one, and its
+/// lengths are the ones the program enforces. Versions with other lengths
+/// belong here once clients write them.
+pub const ENCRYPTION_VERSIONS: &[(&str, usize, usize)] = &[("1.0", SALT_LEN, IV_LEN)];
+
+/// Salt and IV lengths implied by an encryption scheme `version`, `None` for
+/// unknown versions
+pub fn expected_encryption_lengths(version: &str) -> Option<(usize, usize)> {
+    ENCRYPTION_VERSIONS
+        .iter()
+        .find(|(known, _, _)| *known == version)
+        .map(|(_, salt_len, iv_len)| (*salt_len, *iv_len))
+}
+
+/// Rules the program enforces on an instruction's arguments
+pub trait Validate {
+    /// Names of the violated rules, given the base58 `signer` of the
+    /// instruction (its authority account)
+    fn violations(&self, signer: &str) -> Vec<&'static str>;
+}
+
+impl Validate for CreatemailCustom {
+    fn violations(&self, signer: &str) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        check_subject(&self.subject, &mut violations);
+        check_encryption(&self.salt, &self.iv, &mut violations);
+        check_addresses(&self.from.to_string(), &self.to.to_string(), signer, &mut violations);
+        violations
+    }
+}
+
+impl Validate for UpdatemailCustom {
+    fn violations(&self, _signer: &str) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        check_body(&self.body, &mut violations);
+        violations
+    }
+}
+
+impl Validate for SendmailCustom {
+    fn violations(&self, signer: &str) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        check_subject(&self.subject, &mut violations);
+        check_body(&self.body, &mut violations);
+        check_encryption(&self.salt, &self.iv, &mut violations);
+        check_addresses(&self.from.to_string(), &self.to.to_string(), signer, &mut violations);
+        violations
+    }
+}
+
+impl Validate for UpdatemaillabelCustom {
+    fn violations(&self, _signer: &str) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        check_label(self.label, &mut violations);
+        violations
+    }
+}
+
+impl Validate for RegisterCustom {
+    fn violations(&self, _signer: &str) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        check_diffie_key(&self.nostr_key, &mut violations);
+        violations
+    }
+}
+
+impl Validate for RegisterV2Custom {
+    fn violations(&self, _signer: &str) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        check_diffie_key(&self.nostr_key, &mut violations);
+        violations
+    }
+}
+
+impl Validate for UpdateAccountV2Custom {
+    fn violations(&self, _signer: &str) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        check_diffie_key(&self.nostr_key, &mut violations);
+        violations
+    }
+}
+
+// Lengths are compared in bytes, as the program does with `String::len`
+
+fn check_subject(subject: &str, violations: &mut Vec<&'static str>) {
+    if subject.len() > MAX_SUBJECT_LEN {
+        violations.push("InvalidSubject");
+    }
+}
+
+fn check_body(body: &str, violations: &mut Vec<&'static str>) {
+    if body.len() > MAX_BODY_LEN {
+        violations.push("InvalidBody");
+    }
+}
+
+fn check_encryption(salt: &str, iv: &str, violations: &mut Vec<&'static str>) {
+    if salt.len() != SALT_LEN {
+        violations.push("InvalidSalt");
+    }
+    if iv.len() != IV_LEN {
+        violations.push("InvalidIv");
+    }
+}
+
+fn check_diffie_key(key: &str, violations: &mut Vec<&'static str>) {
+    if key.len() != DIFFIE_KEY_LEN {
+        violations.push("InvalidDiffie");
+    }
+}
+
+fn check_label(label: u8, violations: &mut Vec<&'static str>) {
+    if label > MailLabel::Spam as u8 {
+        violations.push("InvalidLabel");
+    }
+}
+
+fn check_addresses(from: &str, to: &str, signer: &str, violations: &mut Vec<&'static str>) {
+    if from == to {
+        violations.push("InvalidDestination");
+    }
+    if !signer.is_empty() && from != signer {
+        violations.push("InvalidSource");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+
+    fn sendmail(from: Pubkey, to: Pubkey) -> SendmailCustom {
+        SendmailCustom {
+            subject: "hello".to_string(),
+            body: "world".to_string(),
+            from,
+            to,
+            salt: "a".repeat(SALT_LEN),
+            iv: "b".repeat(IV_LEN),
+            version: "1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_valid_sendmail() {
+        let from = Pubkey::new_unique();
+        let mail = sendmail(from, Pubkey::new_unique());
+        assert!(mail.violations(&from.to_string()).is_empty());
+    }
+
+    #[test]
+    fn test_sendmail_violations() {
+        let from = Pubkey::new_unique();
+        let mut mail = sendmail(from, from);
+        mail.subject = "s".repeat(MAX_SUBJECT_LEN + 1);
+        mail.body = "b".repeat(MAX_BODY_LEN + 1);
+        mail.salt = "short".to_string();
+        mail.iv = String::new();
+
+        let signer = Pubkey::new_unique().to_string();
+        assert_eq!(mail.violations(&signer), vec![
+            "InvalidSubject",
+            "InvalidBody",
+            "InvalidSalt",
+            "InvalidIv",
+            "InvalidDestination",
+            "InvalidSource",
+        ]);
+    }
+
+    #[test]
+    fn test_diffie_key_length() {
+        let register = RegisterV2Custom { nostr_key: "f".repeat(DIFFIE_KEY_LEN) };
+        assert!(register.violations("").is_empty());
+
+        // An npub is a valid nostr key but not the 64 chars the program expects
+        let register = RegisterV2Custom {
+            nostr_key: "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg".to_string(),
+        };
+        assert_eq!(register.violations(""), vec!["InvalidDiffie"]);
+
+        let register = RegisterV2Custom { nostr_key: "npub1".to_string() };
+        assert_eq!(register.violations(""), vec!["InvalidDiffie"]);
+    }
+
+    #[test]
+    fn test_label() {
+        let label = UpdatemaillabelCustom { label: MailLabel::Spam as u8 };
+        assert!(label.violations("").is_empty());
+
+        let label = UpdatemaillabelCustom { label: MailLabel::Spam as u8 + 1 };
+        assert_eq!(label.violations(""), vec!["InvalidLabel"]);
+    }
+
+    #[test]
+    fn test_expected_encryption_lengths() {
+        assert_eq!(expected_encryption_lengths("1.0"), Some((SALT_LEN, IV_LEN)));
+        assert_eq!(expected_encryption_lengths("2"), None);
+        assert_eq!(expected_encryption_lengths(""), None);
+    }
+}